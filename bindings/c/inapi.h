@@ -284,7 +284,8 @@ extern int8_t file_exists(File *file, Host *host);
  * @param host The Host struct you wish to upload to.
  * @param local_path Absolute path to the local file you wish to upload.
  * @param opts File options struct for controlling upload behaviour.
- * @return Return code - zero on success, non-zero on error.
+ * @return Return code - zero on success, non-zero on error. The
+ *         upload is skipped if the remote file is already identical.
  *
  * #### Usage Example
  *
@@ -337,6 +338,31 @@ extern uint8_t file_upload(File *file, Host *host, const char *local_path, FileO
  */
 extern uint8_t file_upload_file(File *file, Host *host, int file_descriptor, FileOptions *opts);
 
+/**
+ * @brief Atomically replace a file with a local file.
+ *
+ * The new contents are written to a temporary file alongside the
+ * destination, checked with the validation command and renamed into
+ * place, preserving the existing owner and mode. If validation fails,
+ * the original file is left untouched.
+ * @param file The File struct you wish to replace.
+ * @param host The Host struct you wish to replace the file on.
+ * @param local_path Path to the local file containing the new contents.
+ * @param validate Validation command, where `%s` is replaced with the temporary file's path, e.g. `visudo -cf %s`. NULL to skip validation.
+ * @param backup Suffix used to back up the existing file. NULL to skip the backup.
+ * @return Boolean on success (0 or 1) indicating whether the file changed, and -1 on error.
+ */
+extern int8_t file_write_atomic(File *file, Host *host, const char *local_path, const char *validate, const char *backup);
+
+/**
+ * @brief Download a file from the managed host.
+ * @param file The File struct you wish to download.
+ * @param host The Host struct you wish to download from.
+ * @param local_path Absolute path to write the local copy to.
+ * @return Return code - zero on success, non-zero on error.
+ */
+extern uint8_t file_download(File *file, Host *host, const char *local_path);
+
 /**
  * @brief Delete a file.
  * @param file The File struct you wish to delete.
@@ -398,6 +424,14 @@ extern int16_t file_get_mode(File *file, Host *host);
  */
 extern uint8_t file_set_mode(File *file, Host *host, uint16_t mode);
 
+/**
+ * @brief Get the SHA-256 checksum of the file's contents.
+ * @param file The File struct you wish to query.
+ * @param host The Host struct you wish to query a file on.
+ * @return The checksum as a hex string, or NULL on error.
+ */
+extern char *file_checksum(File *file, Host *host);
+
 /**
  * @brief Free a File pointer's memory.
  * @param file The File pointer.
@@ -506,6 +540,28 @@ extern int16_t directory_get_mode(Directory *dir, Host *host);
  */
 extern uint8_t directory_set_mode(Directory *dir, Host *host, uint16_t mode);
 
+/**
+ * @brief Recursively set the owner of the directory and its contents.
+ * @param dir The Directory struct you wish to edit.
+ * @param host The Host struct you wish to edit a directory on.
+ * @param user The user name of the new owner.
+ * @param group The group name of the new owner.
+ * @return The number of entries changed, or -1 on error.
+ */
+extern int64_t directory_set_owner_recursive(Directory *dir, Host *host, char *user, char *group);
+
+/**
+ * @brief Recursively set the permissions of the directory and its
+ * contents. Modes are octal (e.g. "644") or symbolic (e.g. "u+rwX,go-w").
+ * @param dir The Directory struct you wish to edit.
+ * @param host The Host struct you wish to edit a directory on.
+ * @param file_mode The mode for files, or NULL to leave files alone.
+ * @param dir_mode The mode for directories, or NULL to leave
+ * directories alone.
+ * @return The number of entries changed, or -1 on error.
+ */
+extern int64_t directory_set_mode_recursive(Directory *dir, Host *host, char *file_mode, char *dir_mode);
+
 /**
  * @brief Free a Directory pointer's memory.
  * @param dir The Directory pointer.
@@ -513,6 +569,79 @@ extern uint8_t directory_set_mode(Directory *dir, Host *host, uint16_t mode);
  */
 extern uint8_t directory_free(Directory *dir);
 
+/**
+ * @brief Container for operating on a symbolic or hard link.
+ */
+typedef void Link;
+
+/**
+ * @brief Create a new Link struct.
+ * @param host The Host struct you wish to manage a link on.
+ * @param path Absolute path to the link on your managed host.
+ * @return A new Link struct.
+ *
+ * #### Usage Example
+ *
+ * @code
+ * Host *host = host_connect("hosts/myhost.json");
+ * assert(host);
+ *
+ * Link *link = link_new(host, "/srv/app/current");
+ * assert(link);
+ * int8_t rc = link_ensure(link, host, "/srv/app/releases/42", 0);
+ * assert(rc != -1);
+ * @endcode
+ */
+extern Link *link_new(Host *host, const char *path);
+
+/**
+ * @brief Get the path a symbolic link points to.
+ * @param link The Link struct you wish to read.
+ * @param host The Host struct you wish to read a link on.
+ * @return The link's target, an empty string if the path is not a
+ *     symbolic link, or NULL on error.
+ */
+extern char *link_read(Link *link, Host *host);
+
+/**
+ * @brief Ensure the link points to a target, creating or retargeting
+ *     it as needed.
+ * @param link The Link struct you wish to manage.
+ * @param host The Host struct you wish to manage a link on.
+ * @param target Path the link should point to.
+ * @param hard 1 for a hard link, 0 for a symbolic link.
+ * @return 1 if the link changed, 0 if it was already correct and -1
+ *     on error, including if something other than a symbolic link
+ *     exists at the path.
+ */
+extern int8_t link_ensure(Link *link, Host *host, const char *target, uint8_t hard);
+
+/**
+ * @brief Remove a symbolic link.
+ * @param link The Link struct you wish to remove.
+ * @param host The Host struct you wish to remove a link on.
+ * @return 1 if the link was removed, 0 if it did not exist and -1 on
+ *     error, including if the path is not a symbolic link.
+ */
+extern int8_t link_delete(Link *link, Host *host);
+
+/**
+ * @brief Remove a hard link to a target.
+ * @param link The Link struct you wish to remove.
+ * @param host The Host struct you wish to remove a link on.
+ * @param target Path the link shares its inode with.
+ * @return 1 if the link was removed, 0 if it did not exist and -1 on
+ *     error, including if the path is not the same file as target.
+ */
+extern int8_t link_delete_hard(Link *link, Host *host, const char *target);
+
+/**
+ * @brief Free a Link pointer's memory.
+ * @param link The Link pointer.
+ * @return Return code - zero on success, non-zero on error.
+ */
+extern uint8_t link_free(Link *link);
+
 /**
  * @brief A list of supported Package providers.
  */
@@ -525,6 +654,10 @@ enum Providers {
     Pkg,
     Ports,
     Yum,
+    Pip, /**< System-wide Python packages */
+    Gem,
+    Npm, /**< Global Node packages */
+    Cargo,
 };
 
 /**
@@ -583,6 +716,23 @@ extern int8_t package_is_installed(Package *package);
  */
 extern CommandResult *package_install(Package *package, Host *host);
 
+/**
+ * @brief Install a specific version of the package.
+ * @param package The Package struct.
+ * @param host The Host struct you wish to install the package on.
+ * @param version An exact version, or a glob matching a range of versions, e.g. "1.18.*".
+ * @return The CommandResult struct for the operation, or NULL if nothing was done.
+ */
+extern CommandResult *package_install_version(Package *package, Host *host, const char *version);
+
+/**
+ * @brief Upgrade the package to the latest version.
+ * @param package The Package struct.
+ * @param host The Host struct you wish to upgrade the package on.
+ * @return The CommandResult struct for the operation, or NULL if nothing was done.
+ */
+extern CommandResult *package_upgrade(Package *package, Host *host);
+
 /**
  * @brief Uninstall the package.
  * @param package The Package struct.
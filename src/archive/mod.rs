@@ -0,0 +1,385 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Archive primitive.
+
+use command::Command;
+use directory::{Directory, DirectoryTarget};
+use error::{Error, Result};
+use file::{File, FileContent, FileSpec, FileTarget};
+use host::Host;
+use rustc_serialize::hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use target::Target;
+
+
+/// Format of an archive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveFormat {
+    /// Uncompressed tarball (`.tar`)
+    Tar,
+    /// Gzipped tarball (`.tar.gz` or `.tgz`)
+    TarGz,
+    /// Zip file (`.zip`)
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Guess an archive's format from its file extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ArchiveFormat> {
+        let name = match path.as_ref().file_name() {
+            Some(n) => n.to_string_lossy().to_lowercase(),
+            None => return None,
+        };
+
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArchiveFormat::Tar => write!(f, "tar"),
+            ArchiveFormat::TarGz => write!(f, "tar.gz"),
+            ArchiveFormat::Zip => write!(f, "zip"),
+        }
+    }
+}
+
+/// Options for `Archive::extract`.
+#[derive(Default)]
+pub struct ExtractOpts {
+    /// Number of leading path components to remove from each entry,
+    /// like `tar --strip-components`
+    pub strip_components: u32,
+    /// Path, relative to the destination, whose existence means the
+    /// archive has already been extracted
+    pub creates: Option<String>,
+    /// Owner's user name for extracted entries
+    pub user: Option<String>,
+    /// Owner's group name for extracted entries
+    pub group: Option<String>,
+    /// Mode for extracted files, e.g. `644` or `go-w`
+    pub file_mode: Option<String>,
+    /// Mode for extracted directories, e.g. `755` or `u+rwX,go-w`
+    pub dir_mode: Option<String>,
+}
+
+/// Primitive for extracting and creating tar, tar.gz and zip archives.
+///
+///# Example
+///
+/// Deploy a release tarball:
+///
+/// ```no_run
+/// # use inapi::{Archive, Directory, ExtractOpts, Host};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let archive = Archive::new(&mut host, "/tmp/myapp-1.2.0.tar.gz").unwrap();
+/// archive.upload(&mut host, "dist/myapp-1.2.0.tar.gz").unwrap();
+///
+/// let release = Directory::new(&mut host, "/srv/myapp/releases/1.2.0").unwrap();
+/// archive.extract(&mut host, &release, &ExtractOpts {
+///     strip_components: 1,
+///     user: Some("myapp".into()),
+///     group: Some("myapp".into()),
+///     ..Default::default()
+/// }).unwrap();
+/// ```
+///
+/// Back up a directory:
+///
+/// ```no_run
+/// # use inapi::{Archive, Host};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let backup = Archive::new(&mut host, "/var/backups/www.tar.gz").unwrap();
+/// backup.create(&mut host, "/var/www").unwrap();
+/// ```
+pub struct Archive {
+    /// Absolute path to archive on managed host
+    path: PathBuf,
+    /// Archive format
+    format: ArchiveFormat,
+}
+
+impl Archive {
+    /// Create a new Archive struct, guessing the format from the
+    /// path's extension.
+    pub fn new<P: AsRef<Path>>(host: &mut Host, path: P) -> Result<Archive> {
+        match ArchiveFormat::from_path(path.as_ref()) {
+            Some(format) => Archive::with_format(host, path, format),
+            None => Err(Error::Generic(format!("Unknown archive format for `{}`", path.as_ref().display()))),
+        }
+    }
+
+    /// Create a new Archive struct with an explicit format.
+    pub fn with_format<P: AsRef<Path>>(host: &mut Host, path: P, format: ArchiveFormat) -> Result<Archive> {
+        if ! try!(Target::file_is_file(host, path.as_ref())) {
+            return Err(Error::Generic("Path is a directory".to_string()));
+        }
+
+        Ok(Archive {
+            path: path.as_ref().into(),
+            format: format,
+        })
+    }
+
+    /// Get the archive's format.
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Upload a local archive to the managed host. Returns `true` if
+    /// the archive was changed.
+    pub fn upload<P: AsRef<Path>>(&self, host: &mut Host, local_path: P) -> Result<bool> {
+        let file = try!(File::new(host, &self.path));
+        let changes = try!(file.ensure(host, FileSpec {
+            content: Some(FileContent::Path(local_path.as_ref().into())),
+            ..Default::default()
+        }));
+        Ok(!changes.is_empty())
+    }
+
+    /// Extract the archive into a directory, creating it if needed.
+    ///
+    /// Extraction is skipped if the `creates` path exists, or if the
+    /// host's marker for the directory shows that this exact archive
+    /// has already been extracted there. Markers are kept in the
+    /// host's state directory (see `Host::state_dir`), so nothing is
+    /// added to the extracted tree. Returns `true` if the archive was
+    /// extracted.
+    ///
+    /// The archive is unpacked into a scratch directory alongside
+    /// `dir`, where the owner and modes in `opts` are applied, and
+    /// then copied over the top. Entries already in `dir` that aren't
+    /// in the archive are left as they were.
+    pub fn extract(&self, host: &mut Host, dir: &Directory, opts: &ExtractOpts) -> Result<bool> {
+        let dest = dir.path().to_path_buf();
+
+        if let Some(ref creates) = opts.creates {
+            if try!(Target::file_exists(host, &dest.join(creates))) {
+                return Ok(false);
+            }
+        }
+
+        let checksum = try!(Target::file_checksum(host, &self.path));
+        let marker = try!(File::new(host, try!(marker_path(host, &dest))));
+        if try!(marker.exists(host)) && try!(marker.read_to_string(host)).trim() == checksum {
+            return Ok(false);
+        }
+
+        if !try!(dir.exists(host)) {
+            try!(Target::directory_create(host, &dest, true));
+        }
+
+        // Creating the scratch directory fails if anything is already
+        // at the path, so it's always ours to delete.
+        let scratch = try!(scratch_path(&dest));
+        try!(Target::directory_create(host, &scratch, false));
+        let result = self.extract_via(host, dir, &scratch, opts);
+        let _ = Target::directory_delete(host, &scratch, true);
+        try!(result);
+
+        try!(marker.ensure(host, FileSpec {
+            content: Some(FileContent::Bytes(format!("{}\n", checksum).into_bytes())),
+            ..Default::default()
+        }));
+
+        Ok(true)
+    }
+
+    fn extract_via(&self, host: &mut Host, dir: &Directory, scratch: &Path, opts: &ExtractOpts) -> Result<()> {
+        try!(Target::archive_extract(host, &self.path, &scratch.to_path_buf(), self.format, opts.strip_components));
+        let extracted = try!(Directory::new(host, scratch));
+
+        if opts.user.is_some() || opts.group.is_some() {
+            let owner = try!(dir.get_owner(host));
+            let user = opts.user.as_ref().unwrap_or(&owner.user_name);
+            let group = opts.group.as_ref().unwrap_or(&owner.group_name);
+            try!(extracted.set_owner_recursive(host, user, group));
+        }
+
+        if opts.file_mode.is_some() || opts.dir_mode.is_some() {
+            try!(extracted.set_mode_recursive(host, opts.file_mode.as_ref().map(|m| m.as_str()), opts.dir_mode.as_ref().map(|m| m.as_str())));
+        }
+
+        // Copy each entry rather than the scratch directory itself, so
+        // that the destination's own owner and mode are untouched.
+        let cmd = Command::new(&format!("cd {} && find . ! -name . -prune -exec cp -pPR {{}} {}/ \\;",
+                                        quote(scratch), quote(dir.path())));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(())
+    }
+
+    /// Create the archive from the contents of a directory on the
+    /// managed host, replacing any existing archive.
+    ///
+    /// The archive is built alongside the destination and moved into
+    /// place, so a failure never leaves a partial archive behind.
+    pub fn create<P: AsRef<Path>>(&self, host: &mut Host, source: P) -> Result<()> {
+        Target::archive_create(host, &self.path, &source.as_ref().to_path_buf(), self.format)
+    }
+}
+
+/// Get the path of the file recording which archive was last
+/// extracted into `dest`, named after a hash of `dest`.
+fn marker_path(host: &mut Host, dest: &Path) -> Result<PathBuf> {
+    let dir = try!(host.state_dir()).join("archives");
+    try!(Target::directory_create(host, &dir, true));
+
+    let mut hasher = Sha256::default();
+    hasher.input(dest.as_os_str().as_bytes());
+    Ok(dir.join(hasher.result().as_slice().to_hex()))
+}
+
+/// Get a random path alongside `dest` to extract into.
+fn scratch_path(dest: &Path) -> Result<PathBuf> {
+    let mut rand = [0; 8];
+    try!(try!(fs::File::open("/dev/urandom")).read_exact(&mut rand));
+
+    let mut name = OsString::from(".");
+    name.push(dest.file_name().unwrap_or(dest.as_os_str()));
+    name.push(format!(".inapi-extract-{}", rand[..].to_hex()));
+    Ok(dest.with_file_name(name))
+}
+
+/// Quote a path for the shell.
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace("'", "'\\''"))
+}
+
+pub trait ArchiveTarget<P: AsRef<Path>> {
+    fn archive_extract(host: &mut Host, path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()>;
+    fn archive_create(host: &mut Host, path: P, source: P, format: ArchiveFormat) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use czmq::{ZMsg, ZSys};
+    use host::Host;
+    #[cfg(feature = "local-run")]
+    use std::fs;
+    #[cfg(feature = "local-run")]
+    use std::io::Write;
+    #[cfg(feature = "local-run")]
+    use std::os::unix::fs::PermissionsExt;
+    #[cfg(feature = "remote-run")]
+    use std::thread;
+    use super::*;
+    #[cfg(feature = "local-run")]
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ArchiveFormat::from_path("/tmp/app.tar.gz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::from_path("/tmp/app.TGZ"), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::from_path("/tmp/app.tar"), Some(ArchiveFormat::Tar));
+        assert_eq!(ArchiveFormat::from_path("/tmp/app.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(ArchiveFormat::from_path("/tmp/app.rar"), None);
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_extract() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let tempdir = TempDir::new("archive_test_extract").unwrap();
+        let source = tempdir.path().join("source");
+        fs::create_dir_all(source.join("app-1.0/bin")).unwrap();
+        fs::File::create(source.join("app-1.0/bin/run")).unwrap().write_all(b"moo").unwrap();
+
+        let archive = Archive::new(&mut host, tempdir.path().join("app.tar.gz")).unwrap();
+        archive.create(&mut host, &source).unwrap();
+
+        // Existing entries are left alone
+        let release = tempdir.path().join("release");
+        fs::create_dir(&release).unwrap();
+        fs::File::create(release.join("keep")).unwrap();
+        fs::set_permissions(release.join("keep"), fs::Permissions::from_mode(0o644)).unwrap();
+
+        let dir = Directory::new(&mut host, &release).unwrap();
+        let opts = ExtractOpts {
+            strip_components: 1,
+            file_mode: Some("600".into()),
+            ..Default::default()
+        };
+        assert!(archive.extract(&mut host, &dir, &opts).unwrap());
+        assert_eq!(File::new(&mut host, release.join("bin/run")).unwrap().get_mode(&mut host).unwrap(), 600);
+        assert_eq!(File::new(&mut host, release.join("keep")).unwrap().get_mode(&mut host).unwrap(), 644);
+        assert_eq!(fs::read_dir(&release).unwrap().count(), 2);
+        assert_eq!(fs::read_dir(tempdir.path()).unwrap().count(), 3);
+        assert!(!archive.extract(&mut host, &dir, &opts).unwrap());
+
+        // A different archive is extracted over the top
+        fs::File::create(source.join("app-1.0/README")).unwrap();
+        archive.create(&mut host, &source).unwrap();
+        assert!(archive.extract(&mut host, &dir, &opts).unwrap());
+        assert!(tempdir.path().join("release/README").exists());
+
+        fs::remove_file(marker_path(&mut host, &release).unwrap()).unwrap();
+        let opts = ExtractOpts { creates: Some("bin/run".into()), ..Default::default() };
+        assert!(!archive.extract(&mut host, &dir, &opts).unwrap());
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_create() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/www.zip", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("archive::create", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/www.zip", msg.popstr().unwrap().unwrap());
+            assert_eq!("/var/www", msg.popstr().unwrap().unwrap());
+            assert_eq!("zip", msg.popstr().unwrap().unwrap());
+
+            server.send_str("Ok").unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let archive = Archive::new(&mut host, "/tmp/www.zip").unwrap();
+        assert!(archive.create(&mut host, "/var/www").is_ok());
+
+        agent_mock.join().unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use error::Result;
+use super::{ConfigEdit, join, render};
+
+pub fn apply(contents: &str, edits: &[ConfigEdit]) -> Result<Option<String>> {
+    let mut lines: Vec<String> = contents.lines().map(|l| l.to_string()).collect();
+
+    for edit in edits {
+        match *edit {
+            ConfigEdit::Set { ref section, ref key, ref value } => {
+                let value = render(value);
+
+                let range = section_range(&lines, section.as_ref());
+                let (start, end) = match range {
+                    Some(r) => r,
+                    None => {
+                        // Section only returns None when it's named
+                        if lines.last().map_or(false, |l| !l.trim().is_empty()) {
+                            lines.push(String::new());
+                        }
+                        lines.push(format!("[{}]", section.as_ref().unwrap()));
+                        (lines.len(), lines.len())
+                    },
+                };
+
+                let found = (start..end).find(|&i| parse(&lines[i]).map_or(false, |(k, _)| k == key.as_str()));
+                match found {
+                    Some(i) => {
+                        if parse(&lines[i]).unwrap().1 != value {
+                            lines[i] = format!("{} = {}", key, value);
+                        }
+                    },
+                    None => {
+                        // Insert after the last non-blank line in the
+                        // section so that spacing between sections is
+                        // preserved.
+                        let mut pos = end;
+                        while pos > start && lines[pos - 1].trim().is_empty() {
+                            pos -= 1;
+                        }
+                        lines.insert(pos, format!("{} = {}", key, value));
+                    },
+                }
+            },
+            ConfigEdit::Unset { ref section, ref key } => {
+                let range = section_range(&lines, section.as_ref());
+                if let Some((start, end)) = range {
+                    let mut i = end;
+                    while i > start {
+                        i -= 1;
+                        if parse(&lines[i]).map_or(false, |(k, _)| k == key.as_str()) {
+                            lines.remove(i);
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(join(lines, contents))
+}
+
+/// Find the range of lines belonging to a section, excluding its
+/// header. Keys before the first header belong to the `None` section.
+fn section_range(lines: &[String], section: Option<&String>) -> Option<(usize, usize)> {
+    let start = match section {
+        Some(name) => {
+            let header = format!("[{}]", name);
+            match lines.iter().position(|l| l.trim() == header) {
+                Some(i) => i + 1,
+                None => return None,
+            }
+        },
+        None => 0,
+    };
+
+    let end = lines.iter().skip(start).position(|l| l.trim().starts_with("[")).map_or(lines.len(), |i| start + i);
+    Some((start, end))
+}
+
+/// Parse a `key = value` line, ignoring comments.
+fn parse(line: &str) -> Option<(&str, String)> {
+    let line = line.trim();
+    if line.starts_with(";") || line.starts_with("#") {
+        return None;
+    }
+
+    let mut parts = line.splitn(2, '=');
+    let key = parts.next().unwrap().trim();
+    let value = match parts.next() {
+        Some(v) => v.trim(),
+        None => return None,
+    };
+
+    let unquoted = if value.len() >= 2 && value.starts_with("\"") && value.ends_with("\"") {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+
+    Some((key, unquoted.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use config::ConfigEdit;
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let ini = "; PHP config\nengine = On\n\n[Date]\n;date.timezone =\n\n[Session]\nsession.save_path = \"/tmp\"\n";
+
+        let edited = apply(ini, &[
+            ConfigEdit::set_in("Date", "date.timezone", json!("UTC")),
+            ConfigEdit::set_in("Session", "session.save_path", json!("/tmp")),
+            ConfigEdit::set("engine", json!("Off")),
+            ConfigEdit::set_in("opcache", "opcache.enable", json!(1)),
+        ]).unwrap().unwrap();
+        assert_eq!(edited, "; PHP config\nengine = Off\n\n[Date]\n;date.timezone =\ndate.timezone = UTC\n\n[Session]\nsession.save_path = \"/tmp\"\n\n[opcache]\nopcache.enable = 1\n");
+
+        assert!(apply(&edited, &[ConfigEdit::set_in("Date", "date.timezone", json!("UTC"))]).unwrap().is_none());
+
+        let edited = apply(&edited, &[
+            ConfigEdit::unset_in("Date", "date.timezone"),
+            ConfigEdit::unset_in("Missing", "key"),
+        ]).unwrap().unwrap();
+        assert_eq!(edited, "; PHP config\nengine = Off\n\n[Date]\n;date.timezone =\n\n[Session]\nsession.save_path = \"/tmp\"\n\n[opcache]\nopcache.enable = 1\n");
+    }
+}
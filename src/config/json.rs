@@ -0,0 +1,170 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use error::{Error, Result};
+use serde_json::{self, Map, Value};
+use super::ConfigEdit;
+
+pub fn apply(contents: &str, edits: &[ConfigEdit]) -> Result<Option<String>> {
+    let original = if contents.trim().is_empty() {
+        Value::Object(Map::new())
+    } else {
+        try!(serde_json::from_str(contents))
+    };
+    let mut root = original.clone();
+
+    for edit in edits {
+        match *edit {
+            ConfigEdit::Set { ref key, ref value, .. } => try!(set(&mut root, key, value.clone())),
+            ConfigEdit::Unset { ref key, .. } => try!(unset(&mut root, key)),
+        }
+    }
+
+    // Compare values rather than text so that formatting is only
+    // touched when something actually changed.
+    if root == original {
+        Ok(None)
+    } else {
+        let mut json = try!(serde_json::to_string_pretty(&root));
+        json.push('\n');
+        Ok(Some(json))
+    }
+}
+
+fn set(root: &mut Value, pointer: &str, value: Value) -> Result<()> {
+    let tokens = tokens(pointer);
+    let (last, parents) = match tokens.split_last() {
+        Some(t) => t,
+        None => {
+            *root = value;
+            return Ok(());
+        },
+    };
+
+    let mut target = root;
+    for token in parents {
+        let current = target;
+        target = match *current {
+            Value::Object(ref mut map) => {
+                if !map.contains_key(token) {
+                    map.insert(token.clone(), Value::Object(Map::new()));
+                }
+                map.get_mut(token).unwrap()
+            },
+            Value::Array(ref mut arr) => {
+                let i = try!(index(token, arr.len()));
+                try!(arr.get_mut(i).ok_or(Error::Generic(format!("Array index `{}` out of bounds in pointer `{}`", token, pointer))))
+            },
+            _ => return Err(Error::Generic(format!("Cannot traverse scalar value in pointer `{}`", pointer))),
+        };
+    }
+
+    match *target {
+        Value::Object(ref mut map) => {
+            map.insert(last.clone(), value);
+        },
+        Value::Array(ref mut arr) => {
+            let i = if last == "-" { arr.len() } else { try!(index(last, arr.len() + 1)) };
+            if i == arr.len() {
+                arr.push(value);
+            } else {
+                arr[i] = value;
+            }
+        },
+        _ => return Err(Error::Generic(format!("Cannot set a key on a scalar value in pointer `{}`", pointer))),
+    }
+
+    Ok(())
+}
+
+fn unset(root: &mut Value, pointer: &str) -> Result<()> {
+    let tokens = tokens(pointer);
+    let (last, parents) = match tokens.split_last() {
+        Some(t) => t,
+        None => return Err(Error::Generic("Cannot unset the root of a JSON document".into())),
+    };
+
+    let mut target = root;
+    for token in parents {
+        let current = target;
+        target = match *current {
+            Value::Object(ref mut map) => match map.get_mut(token) {
+                Some(v) => v,
+                None => return Ok(()),
+            },
+            Value::Array(ref mut arr) => {
+                let i = try!(index(token, arr.len()));
+                match arr.get_mut(i) {
+                    Some(v) => v,
+                    None => return Ok(()),
+                }
+            },
+            _ => return Ok(()),
+        };
+    }
+
+    match *target {
+        Value::Object(ref mut map) => {
+            map.remove(last);
+        },
+        Value::Array(ref mut arr) => {
+            let i = try!(index(last, arr.len()));
+            if i < arr.len() {
+                arr.remove(i);
+            }
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Split a JSON pointer into unescaped tokens. A leading `/` is
+/// optional.
+fn tokens(pointer: &str) -> Vec<String> {
+    let pointer = pointer.trim_left_matches('/');
+    if pointer.is_empty() {
+        return Vec::new();
+    }
+
+    pointer.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect()
+}
+
+fn index(token: &str, len: usize) -> Result<usize> {
+    match token.parse::<usize>() {
+        Ok(i) => Ok(i),
+        Err(_) => Err(Error::Generic(format!("Invalid array index `{}` for array of length {}", token, len))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::ConfigEdit;
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let config = "{\n  \"db\": {\n    \"host\": \"localhost\"\n  }\n}\n";
+
+        assert!(apply(config, &[ConfigEdit::set("/db/host/name", json!("db1"))]).is_err());
+
+        let edited = apply(config, &[
+            ConfigEdit::set("/db/port", json!(5432)),
+            ConfigEdit::set("/cache/servers", json!([])),
+            ConfigEdit::set("/cache/servers/-", json!("10.0.0.1")),
+            ConfigEdit::unset("/db/host"),
+        ]).unwrap().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&edited).unwrap(), json!({
+            "cache": { "servers": [ "10.0.0.1" ] },
+            "db": { "port": 5432 }
+        }));
+
+        assert!(apply(&edited, &[ConfigEdit::set("/db/port", json!(5432))]).unwrap().is_none());
+        assert!(apply(&edited, &[ConfigEdit::unset("/missing/key")]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use error::Result;
+use super::{ConfigEdit, join, render};
+
+pub fn apply(contents: &str, edits: &[ConfigEdit]) -> Result<Option<String>> {
+    let mut lines: Vec<String> = contents.lines().map(|l| l.to_string()).collect();
+
+    for edit in edits {
+        match *edit {
+            ConfigEdit::Set { ref key, ref value, .. } => {
+                let value = render(value);
+                let found = lines.iter().position(|l| parse(l).map_or(false, |(k, _)| k == key.as_str()));
+
+                match found {
+                    Some(i) => {
+                        if parse(&lines[i]).unwrap().1 != value {
+                            // Preserve `export` prefixes
+                            let export = if lines[i].trim_left().starts_with("export ") { "export " } else { "" };
+                            lines[i] = format!("{}{}={}", export, key, quote(&value));
+                        }
+                    },
+                    None => lines.push(format!("{}={}", key, quote(&value))),
+                }
+            },
+            ConfigEdit::Unset { ref key, .. } => {
+                lines.retain(|l| parse(l).map_or(true, |(k, _)| k != key.as_str()));
+            },
+        }
+    }
+
+    Ok(join(lines, contents))
+}
+
+/// Parse a `KEY=value` line into its key and unquoted value, ignoring
+/// comments.
+fn parse(line: &str) -> Option<(&str, String)> {
+    let mut line = line.trim();
+    if line.starts_with("#") {
+        return None;
+    }
+
+    if line.starts_with("export ") {
+        line = line[7..].trim_left();
+    }
+
+    let mut parts = line.splitn(2, '=');
+    let key = parts.next().unwrap().trim();
+    let value = match parts.next() {
+        Some(v) => v.trim(),
+        None => return None,
+    };
+
+    Some((key, unquote(value)))
+}
+
+/// Quote a value if it contains characters the shell would interpret.
+fn quote(value: &str) -> String {
+    let safe = !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || "_-./:,@%+=".contains(c));
+
+    if safe {
+        value.to_string()
+    } else {
+        let mut quoted = String::from("\"");
+        for c in value.chars() {
+            if "\"\\$`".contains(c) {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+fn unquote(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with("'") && value.ends_with("'") {
+        return value[1..value.len() - 1].to_string();
+    }
+
+    if value.len() >= 2 && value.starts_with("\"") && value.ends_with("\"") {
+        let mut unquoted = String::new();
+        let mut escaped = false;
+        for c in value[1..value.len() - 1].chars() {
+            if c == '\\' && !escaped {
+                escaped = true;
+            } else {
+                unquoted.push(c);
+                escaped = false;
+            }
+        }
+        return unquoted;
+    }
+
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use config::ConfigEdit;
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let defaults = "# Defaults for grub\nGRUB_TIMEOUT=5\nexport GRUB_CMDLINE_LINUX=\"quiet splash\"\n";
+
+        let edited = apply(defaults, &[
+            ConfigEdit::set("GRUB_TIMEOUT", json!(5)),
+            ConfigEdit::set("GRUB_CMDLINE_LINUX", json!("quiet splash $extra")),
+            ConfigEdit::set("GRUB_DISABLE_RECOVERY", json!(true)),
+        ]).unwrap().unwrap();
+        assert_eq!(edited, "# Defaults for grub\nGRUB_TIMEOUT=5\nexport GRUB_CMDLINE_LINUX=\"quiet splash \\$extra\"\nGRUB_DISABLE_RECOVERY=true\n");
+
+        assert!(apply(&edited, &[ConfigEdit::set("GRUB_CMDLINE_LINUX", json!("quiet splash $extra"))]).unwrap().is_none());
+
+        let edited = apply(&edited, &[ConfigEdit::unset("GRUB_TIMEOUT")]).unwrap().unwrap();
+        assert_eq!(edited, "# Defaults for grub\nexport GRUB_CMDLINE_LINUX=\"quiet splash \\$extra\"\nGRUB_DISABLE_RECOVERY=true\n");
+    }
+}
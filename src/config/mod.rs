@@ -0,0 +1,248 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Config primitive for editing structured config files.
+
+mod ini;
+mod json;
+mod keyvalue;
+
+use error::{Error, Result};
+use host::Host;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use target::Target;
+
+/// Format of a config file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ConfigFormat {
+    /// INI file with optional `[section]` headers, e.g. `php.ini`
+    Ini,
+    /// JSON document, where keys are JSON pointers
+    Json,
+    /// Shell style `KEY=value` file, e.g. `/etc/default/*`
+    KeyValue,
+}
+
+/// A single operation on a config file.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ConfigEdit {
+    /// Set a key to a value
+    Set {
+        section: Option<String>,
+        key: String,
+        value: Value,
+    },
+    /// Remove a key
+    Unset {
+        section: Option<String>,
+        key: String,
+    },
+}
+
+impl ConfigEdit {
+    /// Set a top level key, or a JSON pointer for JSON files.
+    pub fn set(key: &str, value: Value) -> ConfigEdit {
+        ConfigEdit::Set { section: None, key: key.into(), value: value }
+    }
+
+    /// Set a key inside an INI section.
+    pub fn set_in(section: &str, key: &str, value: Value) -> ConfigEdit {
+        ConfigEdit::Set { section: Some(section.into()), key: key.into(), value: value }
+    }
+
+    /// Remove a top level key, or a JSON pointer for JSON files.
+    pub fn unset(key: &str) -> ConfigEdit {
+        ConfigEdit::Unset { section: None, key: key.into() }
+    }
+
+    /// Remove a key from an INI section.
+    pub fn unset_in(section: &str, key: &str) -> ConfigEdit {
+        ConfigEdit::Unset { section: Some(section.into()), key: key.into() }
+    }
+
+    fn section(&self) -> Option<&str> {
+        match *self {
+            ConfigEdit::Set { ref section, .. } |
+            ConfigEdit::Unset { ref section, .. } => section.as_ref().map(|s| s.as_str()),
+        }
+    }
+}
+
+/// Primitive for editing keys in structured config files without
+/// templating the whole file.
+///
+/// Comments and ordering are preserved for INI and `KEY=value` files.
+/// JSON files are rewritten with sorted keys, but only when their
+/// contents change.
+///
+///# Example
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_json;
+/// # extern crate inapi;
+/// # use inapi::{ConfigEdit, ConfigFile, ConfigFormat, Host};
+/// # fn main() {
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let php = ConfigFile::new(&mut host, "/etc/php.ini", ConfigFormat::Ini).unwrap();
+/// php.edit(&mut host, &[
+///     ConfigEdit::set_in("Date", "date.timezone", json!("UTC")),
+///     ConfigEdit::unset_in("PHP", "expose_php"),
+/// ]).unwrap();
+///
+/// let app = ConfigFile::new(&mut host, "/srv/app/config.json", ConfigFormat::Json).unwrap();
+/// app.set(&mut host, "/db/port", json!(5432)).unwrap();
+/// # }
+/// ```
+pub struct ConfigFile {
+    /// Absolute path to config file on managed host
+    path: PathBuf,
+    /// Format of config file
+    format: ConfigFormat,
+}
+
+impl ConfigFile {
+    /// Create a new ConfigFile struct.
+    pub fn new<P: AsRef<Path>>(host: &mut Host, path: P, format: ConfigFormat) -> Result<ConfigFile> {
+        if ! try!(Target::file_is_file(host, path.as_ref())) {
+            return Err(Error::Generic("Path is a directory".to_string()));
+        }
+
+        Ok(ConfigFile {
+            path: path.as_ref().into(),
+            format: format,
+        })
+    }
+
+    /// Apply a list of edits to the config file in order. The file is
+    /// created if it does not exist, and is only written if its
+    /// contents changed. Returns `true` if the file was changed.
+    pub fn edit(&self, host: &mut Host, edits: &[ConfigEdit]) -> Result<bool> {
+        Target::config_edit(host, &self.path, self.format, edits)
+    }
+
+    /// Set a key to a value.
+    pub fn set(&self, host: &mut Host, key: &str, value: Value) -> Result<bool> {
+        self.edit(host, &[ConfigEdit::set(key, value)])
+    }
+
+    /// Remove a key.
+    pub fn unset(&self, host: &mut Host, key: &str) -> Result<bool> {
+        self.edit(host, &[ConfigEdit::unset(key)])
+    }
+}
+
+/// Apply a list of edits to a config file's contents, returning the
+/// new contents if they changed.
+pub fn apply(format: ConfigFormat, contents: &str, edits: &[ConfigEdit]) -> Result<Option<String>> {
+    if format != ConfigFormat::Ini && edits.iter().any(|e| e.section().is_some()) {
+        return Err(Error::Generic("Sections are only supported by INI files".into()));
+    }
+
+    match format {
+        ConfigFormat::Ini => ini::apply(contents, edits),
+        ConfigFormat::Json => json::apply(contents, edits),
+        ConfigFormat::KeyValue => keyvalue::apply(contents, edits),
+    }
+}
+
+pub trait ConfigTarget<P: AsRef<Path>> {
+    fn config_edit(host: &mut Host, path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool>;
+}
+
+/// Render a scalar value as it should appear in a line based file.
+fn render(value: &Value) -> String {
+    match *value {
+        Value::String(ref s) => s.clone(),
+        Value::Null => String::new(),
+        ref v => v.to_string(),
+    }
+}
+
+/// Join edited lines back together, returning `None` if they match
+/// the original contents. The original line endings and trailing
+/// newline, or lack of one, are kept, as in `FileEdit::apply`.
+fn join(lines: Vec<String>, original: &str) -> Option<String> {
+    if lines.iter().map(|l| l.as_str()).eq(original.lines()) {
+        return None;
+    }
+
+    let eol = if original.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut joined = lines.join(eol);
+    if !joined.is_empty() && (original.is_empty() || original.ends_with('\n')) {
+        joined.push_str(eol);
+    }
+
+    Some(joined)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use czmq::{ZMsg, ZSys};
+    #[cfg(feature = "remote-run")]
+    use host::Host;
+    #[cfg(feature = "remote-run")]
+    use std::thread;
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let lines = vec!["A=1".to_string(), "B=2".to_string()];
+        assert_eq!(join(lines.clone(), "A=1\r\nB=2\r\n"), None);
+        assert_eq!(join(lines.clone(), "A=1\nB=2"), None);
+        assert_eq!(join(lines.clone(), "A=1\r\n"), Some("A=1\r\nB=2\r\n".into()));
+        assert_eq!(join(lines.clone(), "A=1"), Some("A=1\nB=2".into()));
+        assert_eq!(join(lines, ""), Some("A=1\nB=2\n".into()));
+    }
+
+    #[test]
+    fn test_apply_section() {
+        assert!(apply(ConfigFormat::KeyValue, "", &[ConfigEdit::set_in("a", "b", json!("c"))]).is_err());
+        assert!(apply(ConfigFormat::Ini, "", &[ConfigEdit::set_in("a", "b", json!("c"))]).is_ok());
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_edit() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/etc/default/app", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("config::edit", msg.popstr().unwrap().unwrap());
+            assert_eq!("/etc/default/app", msg.popstr().unwrap().unwrap());
+            assert_eq!("\"KeyValue\"", msg.popstr().unwrap().unwrap());
+            assert_eq!(r#"[{"Unset":{"section":null,"key":"DEBUG"}}]"#, msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("0").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let config = ConfigFile::new(&mut host, "/etc/default/app", ConfigFormat::KeyValue).unwrap();
+        assert!(!config.unset(&mut host, "DEBUG").unwrap());
+
+        agent_mock.join().unwrap();
+    }
+}
@@ -11,7 +11,7 @@
 use ffi_helpers::Leaky;
 use file::ffi::Ffi__FileOwner;
 use host::Host;
-use libc::{c_char, int8_t, int16_t, uint8_t, uint16_t};
+use libc::{c_char, int8_t, int16_t, int64_t, uint8_t, uint16_t};
 use std::convert;
 use std::panic::catch_unwind;
 use super::*;
@@ -134,6 +134,32 @@ pub extern "C" fn directory_set_mode(dir_ptr: *const Directory, host_ptr: *const
     0
 }
 
+#[no_mangle]
+pub extern "C" fn directory_set_owner_recursive(dir_ptr: *const Directory,
+                                                host_ptr: *const Host,
+                                                user_ptr: *const c_char,
+                                                group_ptr: *const c_char) -> int64_t {
+    let directory = Leaky::new(tryrc!(readptr!(dir_ptr, "Directory pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+    let user = tryrc!(ptrtostr!(user_ptr, "user string"), -1);
+    let group = tryrc!(ptrtostr!(group_ptr, "group string"), -1);
+
+    tryrc!(directory.set_owner_recursive(&mut host, user, group), -1) as i64
+}
+
+#[no_mangle]
+pub extern "C" fn directory_set_mode_recursive(dir_ptr: *const Directory,
+                                               host_ptr: *const Host,
+                                               file_mode_ptr: *const c_char,
+                                               dir_mode_ptr: *const c_char) -> int64_t {
+    let directory = Leaky::new(tryrc!(readptr!(dir_ptr, "Directory pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+    let file_mode = if file_mode_ptr.is_null() { None } else { Some(tryrc!(ptrtostr!(file_mode_ptr, "file mode string"), -1)) };
+    let dir_mode = if dir_mode_ptr.is_null() { None } else { Some(tryrc!(ptrtostr!(dir_mode_ptr, "directory mode string"), -1)) };
+
+    tryrc!(directory.set_mode_recursive(&mut host, file_mode, dir_mode), -1) as i64
+}
+
 #[no_mangle]
 pub extern "C" fn directory_free(dir_ptr: *mut Directory) -> uint8_t {
     tryrc!(boxptr!(dir_ptr, "Directory pointer"));
@@ -9,10 +9,14 @@
 //! Directory primitive.
 
 pub mod ffi;
+pub mod mode;
 
 use error::{Error, Result};
-use file::FileOwner;
+use file::{attr, AclEntry, File, FileChange, FileContent, FileOwner, FileSpec, FileStat, FileTarget};
+use glob::Pattern;
 use host::Host;
+use std::collections::BTreeSet;
+use std::fs;
 use std::path::{Path, PathBuf};
 use target::Target;
 
@@ -22,6 +26,39 @@ pub enum DirectoryOpts {
     DoRecursive,
 }
 
+/// Options for `Directory::sync`.
+#[derive(Default)]
+pub struct SyncOpts {
+    /// Delete remote entries that don't exist in the local tree, and
+    /// replace remote files that are directories locally, or vice
+    /// versa
+    pub delete: bool,
+    /// User to own every synced entry
+    pub user: Option<String>,
+    /// Group to own every synced entry
+    pub group: Option<String>,
+    /// Permissions mask for synced files
+    pub file_mode: Option<u16>,
+    /// Permissions mask for synced directories
+    pub dir_mode: Option<u16>,
+}
+
+/// A change made by `Directory::sync`. Paths are relative to the
+/// synced directory, with `.` being the directory itself.
+#[derive(Debug, PartialEq)]
+pub enum SyncChange {
+    /// Entry did not exist and was created
+    Created(PathBuf),
+    /// File contents were replaced
+    Updated(PathBuf),
+    /// Extraneous entry was deleted
+    Deleted(PathBuf),
+    /// Owner was changed
+    Owner(PathBuf),
+    /// Permissions mask was changed
+    Mode(PathBuf),
+}
+
 /// Primitive for managing directories.
 ///
 ///# Examples
@@ -64,6 +101,11 @@ impl Directory {
         })
     }
 
+    /// Get the directory's path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Check if the directory exists.
     pub fn exists(&self, host: &mut Host) -> Result<bool> {
         Target::directory_exists(host, &self.path)
@@ -126,6 +168,318 @@ impl Directory {
     pub fn set_mode(&self, host: &mut Host, mode: u16) -> Result<()> {
         Target::directory_set_mode(host, &self.path, mode)
     }
+
+    /// Get the directory's metadata.
+    pub fn stat(&self, host: &mut Host) -> Result<FileStat> {
+        Target::file_stat(host, &self.path)
+    }
+
+    /// Get the directory's extended POSIX ACL entries, including
+    /// default entries.
+    pub fn get_acl(&self, host: &mut Host) -> Result<Vec<AclEntry>> {
+        Target::file_get_acl(host, &self.path)
+    }
+
+    /// Add or update ACL entries. Default entries, which are
+    /// inherited by new files and directories, are also supported.
+    /// Non-default base entries and the mask are part of the mode, so
+    /// passing them is an error. Returns `true` if the ACL changed.
+    pub fn ensure_acl(&self, host: &mut Host, entries: &[AclEntry]) -> Result<bool> {
+        attr::ensure_acl(host, &self.path, entries)
+    }
+
+    /// Remove the ACL entries for the given users and groups,
+    /// ignoring permissions. Returns `true` if the ACL changed.
+    pub fn remove_acl(&self, host: &mut Host, entries: &[AclEntry]) -> Result<bool> {
+        attr::remove_acl(host, &self.path, entries)
+    }
+
+    /// Get an extended attribute.
+    pub fn get_xattr(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        Target::file_get_xattr(host, &self.path, name)
+    }
+
+    /// Set an extended attribute. Returns `true` if it changed.
+    pub fn set_xattr(&self, host: &mut Host, name: &str, value: &str) -> Result<bool> {
+        attr::set_xattr(host, &self.path, name, value)
+    }
+
+    /// Remove an extended attribute. Returns `true` if it existed.
+    pub fn remove_xattr(&self, host: &mut Host, name: &str) -> Result<bool> {
+        attr::remove_xattr(host, &self.path, name)
+    }
+
+    /// Get the directory's SELinux context, or `None` if it has none.
+    pub fn get_selinux_context(&self, host: &mut Host) -> Result<Option<String>> {
+        Target::file_get_selinux_context(host, &self.path)
+    }
+
+    /// Set the directory's SELinux context. Returns `true` if it
+    /// changed.
+    pub fn set_selinux_context(&self, host: &mut Host, context: &str) -> Result<bool> {
+        attr::set_selinux_context(host, &self.path, context)
+    }
+
+    /// Reset the SELinux context of the directory, and optionally
+    /// everything beneath it, to the policy default, like
+    /// `restorecon`. Returns `true` if anything changed.
+    pub fn restore_selinux_context(&self, host: &mut Host, recursive: bool) -> Result<bool> {
+        Target::directory_restore_selinux_context(host, &self.path, recursive)
+    }
+
+    /// List the directory's contents.
+    ///
+    /// Paths are relative to the directory, sorted, and directories
+    /// end with a `/`. Symlinks are listed but never followed.
+    ///
+    /// If `pattern` is given, only matching entries are returned. A
+    /// pattern containing a `/` is matched against the relative path,
+    /// otherwise it is matched against the entry's name.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{Directory, Host};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let dir = Directory::new(&mut host, "/etc/nginx").unwrap();
+    /// for conf in dir.list(&mut host, true, Some("*.conf")).unwrap() {
+    ///     let stat = dir.stat_entry(&mut host, &conf).unwrap();
+    ///     println!("{} ({} bytes)", conf, stat.size);
+    /// }
+    /// ```
+    pub fn list(&self, host: &mut Host, recursive: bool, pattern: Option<&str>) -> Result<Vec<String>> {
+        let entries = try!(Target::directory_list(host, &self.path, recursive));
+
+        match pattern {
+            Some(p) => {
+                let glob = try!(Pattern::new(p).map_err(|e| Error::Generic(format!("Invalid pattern `{}`: {}", p, e))));
+                let match_path = p.contains('/');
+
+                Ok(entries.into_iter().filter(|entry| {
+                    let entry = entry.trim_right_matches('/');
+                    if match_path {
+                        glob.matches(entry)
+                    } else {
+                        glob.matches(entry.rsplit('/').next().unwrap())
+                    }
+                }).collect())
+            },
+            None => Ok(entries),
+        }
+    }
+
+    /// Get the metadata of an entry in the directory, as returned by
+    /// `list`.
+    pub fn stat_entry(&self, host: &mut Host, entry: &str) -> Result<FileStat> {
+        Target::file_stat(host, &self.path.join(entry.trim_right_matches('/')))
+    }
+
+    /// Recursively set the owner of the directory and everything
+    /// beneath it. Symlinks are changed rather than followed.
+    ///
+    /// Returns the number of entries that changed.
+    pub fn set_owner_recursive(&self, host: &mut Host, user: &str, group: &str) -> Result<u64> {
+        Target::directory_set_owner_recursive(host, &self.path, user, group)
+    }
+
+    /// Recursively set the permissions of the directory and
+    /// everything beneath it, with separate modes for files and
+    /// directories. A mode of `None` leaves that type of entry alone.
+    ///
+    /// Modes are either octal, e.g. `644`, or symbolic, e.g.
+    /// `u+rwX,go-w`. Symlinks are skipped. Returns the number of
+    /// entries that changed.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{Directory, Host};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let dir = Directory::new(&mut host, "/var/www").unwrap();
+    /// dir.set_mode_recursive(&mut host, Some("644"), Some("755")).unwrap();
+    /// dir.set_mode_recursive(&mut host, Some("go-w"), Some("u+rwX,go-w")).unwrap();
+    /// ```
+    pub fn set_mode_recursive(&self, host: &mut Host, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+        Target::directory_set_mode_recursive(host, &self.path, file_mode, dir_mode)
+    }
+
+    /// Mirror a local directory tree to the managed host.
+    ///
+    /// Files are only uploaded if their checksums differ. Returns
+    /// each change that was made.
+    ///
+    /// If an entry is a file on one side and a directory on the other,
+    /// the remote entry is replaced when `delete` is set. Otherwise an
+    /// error is returned before anything is changed.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{Directory, Host, SyncOpts};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let dir = Directory::new(&mut host, "/var/www/static").unwrap();
+    /// let changes = dir.sync(&mut host, "assets", &SyncOpts {
+    ///     delete: true,
+    ///     user: Some("www-data".into()),
+    ///     file_mode: Some(644),
+    ///     dir_mode: Some(755),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// ```
+    pub fn sync<P: AsRef<Path>>(&self, host: &mut Host, local_dir: P, opts: &SyncOpts) -> Result<Vec<SyncChange>> {
+        let local_dir = local_dir.as_ref();
+        let mut changes = Vec::new();
+
+        let exists = try!(self.exists(host));
+        let local: BTreeSet<String> = try!(list_local(local_dir, true)).into_iter().collect();
+        let remote: BTreeSet<String> = if exists {
+            try!(Target::directory_list(host, &self.path, true)).into_iter().collect()
+        } else {
+            BTreeSet::new()
+        };
+
+        // Remote entries that are a file locally and a directory
+        // remotely, or vice versa
+        let mismatched: BTreeSet<String> = local.iter().map(|e| {
+            if e.ends_with("/") {
+                e.trim_right_matches('/').to_string()
+            } else {
+                format!("{}/", e)
+            }
+        }).filter(|e| remote.contains(e)).collect();
+
+        if !mismatched.is_empty() && !opts.delete {
+            let entries: Vec<&str> = mismatched.iter().map(|e| e.trim_right_matches('/')).collect();
+            return Err(Error::Generic(format!("Cannot sync entries that are files on one side and directories on the other: {}", entries.join(", "))));
+        }
+
+        if !exists {
+            try!(Target::directory_create(host, &self.path, true));
+            changes.push(SyncChange::Created(PathBuf::from(".")));
+        }
+        try!(sync_attrs(host, &self.path, Path::new("."), opts, &mut changes));
+
+        // Clear mismatched entries out of the way first
+        let mut deleted: Vec<&String> = Vec::new();
+        for entry in &mismatched {
+            let rel = PathBuf::from(entry.trim_right_matches('/'));
+            if entry.ends_with("/") {
+                try!(Target::directory_delete(host, &self.path.join(&rel), true));
+                deleted.push(entry);
+            } else {
+                try!(Target::file_delete(host, &self.path.join(&rel)));
+            }
+            changes.push(SyncChange::Deleted(rel));
+        }
+
+        for entry in &local {
+            let rel = PathBuf::from(entry.trim_right_matches('/'));
+            let remote_path = self.path.join(&rel);
+
+            if entry.ends_with("/") {
+                if !remote.contains(entry) {
+                    try!(Target::directory_create(host, &remote_path, false));
+                    changes.push(SyncChange::Created(rel.clone()));
+                }
+                try!(sync_attrs(host, &remote_path, &rel, opts, &mut changes));
+            } else {
+                let file = try!(File::new(host, &remote_path));
+                let file_changes = try!(file.ensure(host, FileSpec {
+                    content: Some(FileContent::Path(local_dir.join(&rel))),
+                    user: opts.user.clone(),
+                    group: opts.group.clone(),
+                    mode: opts.file_mode,
+                    ..Default::default()
+                }));
+
+                for change in file_changes {
+                    changes.push(match change {
+                        FileChange::Created => SyncChange::Created(rel.clone()),
+                        FileChange::Content => SyncChange::Updated(rel.clone()),
+                        FileChange::Owner(..) => SyncChange::Owner(rel.clone()),
+                        FileChange::Mode(_) => SyncChange::Mode(rel.clone()),
+                    });
+                }
+            }
+        }
+
+        if opts.delete {
+            // Entries are sorted, so a directory is always deleted
+            // before its children are visited.
+            for entry in &remote {
+                if local.contains(entry) || mismatched.contains(entry) || deleted.iter().any(|d| entry.starts_with(d.as_str())) {
+                    continue;
+                }
+
+                let rel = PathBuf::from(entry.trim_right_matches('/'));
+                if entry.ends_with("/") {
+                    try!(Target::directory_delete(host, &self.path.join(&rel), true));
+                    deleted.push(entry);
+                } else {
+                    try!(Target::file_delete(host, &self.path.join(&rel)));
+                }
+                changes.push(SyncChange::Deleted(rel));
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+/// List a local directory. Paths are relative to `path`, sorted, and
+/// directories end with a `/`. Symlinks are not followed.
+pub fn list_local<P: AsRef<Path>>(path: P, recursive: bool) -> Result<Vec<String>> {
+    let mut entries = Vec::new();
+    try!(list_into(path.as_ref(), path.as_ref(), recursive, &mut entries));
+    entries.sort();
+    Ok(entries)
+}
+
+fn list_into(root: &Path, dir: &Path, recursive: bool, entries: &mut Vec<String>) -> Result<()> {
+    for entry in try!(fs::read_dir(dir)) {
+        let entry = try!(entry);
+        let path = entry.path();
+        let rel = path.strip_prefix(root).unwrap().to_string_lossy().into_owned();
+
+        if try!(entry.file_type()).is_dir() {
+            entries.push(format!("{}/", rel));
+            if recursive {
+                try!(list_into(root, &path, recursive, entries));
+            }
+        } else {
+            entries.push(rel);
+        }
+    }
+
+    Ok(())
+}
+
+fn sync_attrs(host: &mut Host, path: &Path, rel: &Path, opts: &SyncOpts, changes: &mut Vec<SyncChange>) -> Result<()> {
+    if opts.user.is_some() || opts.group.is_some() {
+        let owner = try!(Target::directory_get_owner(host, path));
+        let user = opts.user.as_ref().unwrap_or(&owner.user_name);
+        let group = opts.group.as_ref().unwrap_or(&owner.group_name);
+
+        if *user != owner.user_name || *group != owner.group_name {
+            try!(Target::directory_set_owner(host, path, user, group));
+            changes.push(SyncChange::Owner(rel.into()));
+        }
+    }
+
+    if let Some(mode) = opts.dir_mode {
+        if try!(Target::directory_get_mode(host, path)) != mode {
+            try!(Target::directory_set_mode(host, path, mode));
+            changes.push(SyncChange::Mode(rel.into()));
+        }
+    }
+
+    Ok(())
 }
 
 pub trait DirectoryTarget<P: AsRef<Path>> {
@@ -138,6 +492,10 @@ pub trait DirectoryTarget<P: AsRef<Path>> {
     fn directory_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()>;
     fn directory_get_mode(host: &mut Host, path: P) -> Result<u16>;
     fn directory_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()>;
+    fn directory_list(host: &mut Host, path: P, recursive: bool) -> Result<Vec<String>>;
+    fn directory_set_owner_recursive(host: &mut Host, path: P, user: &str, group: &str) -> Result<u64>;
+    fn directory_set_mode_recursive(host: &mut Host, path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64>;
+    fn directory_restore_selinux_context(host: &mut Host, path: P, recursive: bool) -> Result<bool>;
 }
 
 #[cfg(test)]
@@ -145,9 +503,13 @@ mod tests {
     use Host;
     #[cfg(feature = "remote-run")]
     use czmq::{ZMsg, ZSys};
+    use std::fs;
+    #[cfg(feature = "local-run")]
+    use std::io::Write;
     use super::*;
     #[cfg(feature = "remote-run")]
     use std::thread;
+    use tempdir::TempDir;
 
     #[cfg(feature = "local-run")]
     #[test]
@@ -158,6 +520,137 @@ mod tests {
         assert!(dir.is_ok());
     }
 
+    #[test]
+    fn test_list_local() {
+        let tempdir = TempDir::new("directory_test_list_local").unwrap();
+        fs::create_dir(tempdir.path().join("b")).unwrap();
+        fs::File::create(tempdir.path().join("b/c")).unwrap();
+        fs::File::create(tempdir.path().join("a")).unwrap();
+
+        assert_eq!(list_local(tempdir.path(), true).unwrap(), vec!["a", "b/", "b/c"]);
+        assert_eq!(list_local(tempdir.path(), false).unwrap(), vec!["a", "b/"]);
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_sync() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let local = TempDir::new("directory_test_sync_local").unwrap();
+        fs::create_dir(local.path().join("css")).unwrap();
+        fs::File::create(local.path().join("css/site.css")).unwrap().write_all(b"body {}").unwrap();
+        fs::File::create(local.path().join("index.html")).unwrap().write_all(b"moo").unwrap();
+
+        let remote = TempDir::new("directory_test_sync_remote").unwrap();
+        fs::create_dir(remote.path().join("old")).unwrap();
+        fs::File::create(remote.path().join("old/file")).unwrap();
+        fs::File::create(remote.path().join("index.html")).unwrap().write_all(b"cow").unwrap();
+
+        let dir = Directory::new(&mut host, remote.path()).unwrap();
+        let opts = SyncOpts { delete: true, ..Default::default() };
+
+        let changes = dir.sync(&mut host, local.path(), &opts).unwrap();
+        assert_eq!(changes, vec![
+            SyncChange::Created("css".into()),
+            SyncChange::Created("css/site.css".into()),
+            SyncChange::Updated("index.html".into()),
+            SyncChange::Deleted("old".into()),
+        ]);
+        assert_eq!(list_local(remote.path(), true).unwrap(), vec!["css/", "css/site.css", "index.html"]);
+
+        assert!(dir.sync(&mut host, local.path(), &opts).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_sync_type_mismatch() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let local = TempDir::new("directory_test_sync_mismatch_local").unwrap();
+        fs::create_dir(local.path().join("conf")).unwrap();
+        fs::File::create(local.path().join("conf/site.conf")).unwrap();
+        fs::File::create(local.path().join("logs")).unwrap();
+
+        let remote = TempDir::new("directory_test_sync_mismatch_remote").unwrap();
+        fs::File::create(remote.path().join("conf")).unwrap();
+        fs::create_dir(remote.path().join("logs")).unwrap();
+        fs::File::create(remote.path().join("logs/old.log")).unwrap();
+
+        let dir = Directory::new(&mut host, remote.path()).unwrap();
+
+        // Nothing is touched unless deleting is allowed
+        assert!(dir.sync(&mut host, local.path(), &SyncOpts::default()).is_err());
+        assert_eq!(list_local(remote.path(), true).unwrap(), vec!["conf", "logs/", "logs/old.log"]);
+
+        let opts = SyncOpts { delete: true, ..Default::default() };
+        let changes = dir.sync(&mut host, local.path(), &opts).unwrap();
+        assert_eq!(changes, vec![
+            SyncChange::Deleted("conf".into()),
+            SyncChange::Deleted("logs".into()),
+            SyncChange::Created("conf".into()),
+            SyncChange::Created("conf/site.conf".into()),
+            SyncChange::Created("logs".into()),
+        ]);
+        assert_eq!(list_local(remote.path(), true).unwrap(), vec!["conf/", "conf/site.conf", "logs"]);
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_list_stat() {
+        use file::FileType;
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let tempdir = TempDir::new("directory_test_list_stat").unwrap();
+        fs::create_dir(tempdir.path().join("conf.d")).unwrap();
+        fs::File::create(tempdir.path().join("conf.d/site.conf")).unwrap().write_all(b"moo").unwrap();
+        fs::File::create(tempdir.path().join("main.conf")).unwrap();
+        symlink("main.conf", tempdir.path().join("link")).unwrap();
+        fs::set_permissions(tempdir.path().join("conf.d/site.conf"), fs::Permissions::from_mode(0o4750)).unwrap();
+
+        let dir = Directory::new(&mut host, tempdir.path()).unwrap();
+        assert_eq!(dir.list(&mut host, false, None).unwrap(), vec!["conf.d/", "link", "main.conf"]);
+        assert_eq!(dir.list(&mut host, true, Some("*.conf")).unwrap(), vec!["conf.d/site.conf", "main.conf"]);
+        assert_eq!(dir.list(&mut host, true, Some("conf.d/*")).unwrap(), vec!["conf.d/site.conf"]);
+        assert!(dir.list(&mut host, true, Some("[")).is_err());
+
+        let stat = dir.stat_entry(&mut host, "conf.d/site.conf").unwrap();
+        assert_eq!(stat.file_type, FileType::File);
+        assert_eq!(stat.size, 3);
+        assert_eq!(stat.mode, 4750);
+        assert!(stat.inode > 0);
+
+        assert_eq!(dir.stat_entry(&mut host, "link").unwrap().file_type, FileType::Symlink);
+        assert_eq!(dir.stat(&mut host).unwrap().file_type, FileType::Directory);
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_set_mode_recursive() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let tempdir = TempDir::new("directory_test_set_mode_recursive").unwrap();
+        fs::create_dir(tempdir.path().join("bin")).unwrap();
+        fs::File::create(tempdir.path().join("bin/run")).unwrap();
+        fs::set_permissions(tempdir.path().join("bin/run"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(tempdir.path().join("bin"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(tempdir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let dir = Directory::new(&mut host, tempdir.path()).unwrap();
+        assert_eq!(dir.set_mode_recursive(&mut host, Some("go-rwx"), Some("750")).unwrap(), 3);
+        assert_eq!(fs::metadata(tempdir.path().join("bin/run")).unwrap().permissions().mode() & 0o7777, 0o700);
+        assert_eq!(fs::metadata(tempdir.path().join("bin")).unwrap().permissions().mode() & 0o7777, 0o750);
+        assert_eq!(dir.set_mode_recursive(&mut host, Some("go-rwx"), Some("750")).unwrap(), 0);
+        assert_eq!(dir.set_mode_recursive(&mut host, None, Some("u+rwX,go-w")).unwrap(), 0);
+    }
+
     #[cfg(feature = "remote-run")]
     #[test]
     fn test_new_ok() {
@@ -478,4 +971,41 @@ mod tests {
 
         agent_mock.join().unwrap();
     }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_set_mode_recursive() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let req = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("directory::is_directory", req.popstr().unwrap().unwrap());
+            assert_eq!("/path/to/dir", req.popstr().unwrap().unwrap());
+
+            let rep = ZMsg::new();
+            rep.addstr("Ok").unwrap();
+            rep.addstr("1").unwrap();
+            rep.send(&mut server).unwrap();
+
+            let req = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("directory::set_mode_recursive", req.popstr().unwrap().unwrap());
+            assert_eq!("/path/to/dir", req.popstr().unwrap().unwrap());
+            assert_eq!("", req.popstr().unwrap().unwrap());
+            assert_eq!("u+rwX,go-w", req.popstr().unwrap().unwrap());
+
+            let rep = ZMsg::new();
+            rep.addstr("Ok").unwrap();
+            rep.addstr("12").unwrap();
+            rep.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let dir = Directory::new(&mut host, "/path/to/dir").unwrap();
+        assert_eq!(dir.set_mode_recursive(&mut host, None, Some("u+rwX,go-w")).unwrap(), 12);
+
+        agent_mock.join().unwrap();
+    }
 }
@@ -0,0 +1,115 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Parser for `chmod` style permission modes.
+
+use error::{Error, Result};
+
+/// Apply a mode to an entry's current permissions, returning the new
+/// permission bits.
+///
+/// `spec` is either an octal mode, e.g. `644`, or a comma separated
+/// list of symbolic clauses, e.g. `u+rwX,go-w`. Unlike `chmod`, a
+/// clause without a user class applies to all classes regardless of
+/// the umask.
+pub fn apply(spec: &str, current: u32, is_dir: bool) -> Result<u32> {
+    if !spec.is_empty() && spec.chars().all(|c| c.is_digit(8)) {
+        return match u32::from_str_radix(spec, 8) {
+            Ok(m) if m <= 0o7777 => Ok(m),
+            _ => Err(invalid(spec)),
+        };
+    }
+
+    let mut mode = current & 0o7777;
+
+    for clause in spec.split(',') {
+        let who_end = clause.find(|c: char| !"ugoa".contains(c)).unwrap_or(clause.len());
+        let mut mask = 0;
+        for c in clause[..who_end].chars() {
+            mask |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                _ => 0o7777,
+            };
+        }
+        if mask == 0 {
+            mask = 0o7777;
+        }
+
+        let ops = &clause[who_end..];
+        if ops.is_empty() {
+            return Err(invalid(spec));
+        }
+
+        let mut chars = ops.chars().peekable();
+        while let Some(op) = chars.next() {
+            if !"+-=".contains(op) {
+                return Err(invalid(spec));
+            }
+
+            let mut bits = 0;
+            while let Some(&c) = chars.peek() {
+                if "+-=".contains(c) {
+                    break;
+                }
+
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    'X' if is_dir || mode & 0o111 != 0 => 0o111,
+                    'X' => 0,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    _ => return Err(invalid(spec)),
+                };
+                chars.next();
+            }
+            bits &= mask;
+
+            match op {
+                '+' => mode |= bits,
+                '-' => mode &= !bits,
+                _ => mode = (mode & !mask) | bits,
+            }
+        }
+    }
+
+    Ok(mode)
+}
+
+fn invalid(spec: &str) -> Error {
+    Error::Generic(format!("Invalid mode `{}`", spec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(apply("644", 0o100755, false).unwrap(), 0o644);
+        assert_eq!(apply("2775", 0o755, true).unwrap(), 0o2775);
+        assert!(apply("9", 0o644, false).is_err());
+        assert!(apply("17777", 0o644, false).is_err());
+
+        assert_eq!(apply("u+rwX,go-w", 0o666, false).unwrap(), 0o644);
+        assert_eq!(apply("u+rwX,go-w", 0o666, true).unwrap(), 0o744);
+        assert_eq!(apply("u+rwX,go-w", 0o676, false).unwrap(), 0o754);
+        assert_eq!(apply("a+X", 0o744, false).unwrap(), 0o755);
+        assert_eq!(apply("go=r", 0o777, false).unwrap(), 0o744);
+        assert_eq!(apply("u=rw,g+s", 0o755, true).unwrap(), 0o2655);
+        assert_eq!(apply("+t", 0o777, true).unwrap(), 0o1777);
+        assert_eq!(apply("o-rwx+r", 0o777, false).unwrap(), 0o774);
+
+        assert!(apply("u", 0o644, false).is_err());
+        assert!(apply("u+q", 0o644, false).is_err());
+        assert!(apply("z+r", 0o644, false).is_err());
+    }
+}
@@ -8,6 +8,7 @@
 
 #[cfg(feature = "remote-run")]
 use czmq;
+use host::data::SchemaViolation;
 use libc::c_char;
 use mustache;
 use regex;
@@ -82,6 +83,8 @@ pub enum Error {
     QueryParser(String),
     /// Regex error
     Regex(regex::Error),
+    /// Data failed payload schema validation
+    Schema(Vec<SchemaViolation>),
     /// Serde JSON error
     SerdeJson(serde_json::Error),
     /// Cast str
@@ -123,6 +126,13 @@ impl fmt::Display for Error {
             Error::ParseInt(ref e) => write!(f, "Parse error: {}", e),
             Error::QueryParser(ref e) => write!(f, "Query parser error: {}", e),
             Error::Regex(ref e) => write!(f, "Regex error: {}", e),
+            Error::Schema(ref v) => {
+                try!(write!(f, "Data failed schema validation:"));
+                for violation in v {
+                    try!(write!(f, "\n    {}", violation));
+                }
+                Ok(())
+            },
             Error::SerdeJson(ref e) => write!(f, "Serde JSON error: {}", e),
             Error::StrFromUtf8(ref e) => write!(f, "Convert from UTF8 slice to str error: {}", e),
             Error::StringFromUtf8(ref e) => write!(f, "Convert from UTF8 slice to String error: {}", e),
@@ -160,6 +170,7 @@ impl error::Error for Error {
             Error::ParseInt(ref e) => e.description(),
             Error::QueryParser(ref e) => e,
             Error::Regex(ref e) => e.description(),
+            Error::Schema(_) => "Data failed schema validation",
             Error::SerdeJson(ref e) => e.description(),
             Error::StrFromUtf8(ref e) => e.description(),
             Error::StringFromUtf8(ref e) => e.description(),
@@ -0,0 +1,251 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! ACLs, extended attributes and SELinux contexts, shared by `File`
+//! and `Directory`.
+
+use error::{Error, Result};
+use host::Host;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use super::FileTarget;
+use target::Target;
+
+/// Type of a POSIX ACL entry.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AclKind {
+    /// Permissions for a user, or the owner if no qualifier is given
+    User,
+    /// Permissions for a group, or the owning group if no qualifier
+    /// is given
+    Group,
+    /// Upper bound on group and named user permissions
+    Mask,
+    /// Permissions for everyone else
+    Other,
+}
+
+/// A POSIX ACL entry, e.g. `user:deploy:rwx` or
+/// `default:group:www-data:r-x`.
+///
+/// Entries can be parsed from and formatted to `setfacl` syntax,
+/// including its abbreviated forms, e.g. `d:g:www-data:rx`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AclEntry {
+    /// Whether this is a default ACL entry, which is inherited by new
+    /// entries in a directory
+    pub default: bool,
+    /// Entry type
+    pub kind: AclKind,
+    /// User or group name
+    pub qualifier: Option<String>,
+    /// Permissions bitmask, where read = 4, write = 2 and execute = 1
+    pub perms: u8,
+}
+
+impl AclEntry {
+    /// Create a new entry granting a user permissions.
+    pub fn user(name: &str, perms: u8) -> AclEntry {
+        AclEntry { default: false, kind: AclKind::User, qualifier: Some(name.into()), perms: perms }
+    }
+
+    /// Create a new entry granting a group permissions.
+    pub fn group(name: &str, perms: u8) -> AclEntry {
+        AclEntry { default: false, kind: AclKind::Group, qualifier: Some(name.into()), perms: perms }
+    }
+
+    /// Convert this entry into a default entry.
+    pub fn into_default(mut self) -> AclEntry {
+        self.default = true;
+        self
+    }
+
+    /// Whether the entry applies to the same user or group as
+    /// `other`, regardless of permissions.
+    pub fn same_subject(&self, other: &AclEntry) -> bool {
+        self.default == other.default && self.kind == other.kind && self.qualifier == other.qualifier
+    }
+
+    /// Format the entry without its permissions, as `setfacl -x`
+    /// expects.
+    pub fn subject(&self) -> String {
+        format!("{}{}:{}",
+                if self.default { "default:" } else { "" },
+                match self.kind {
+                    AclKind::User => "user",
+                    AclKind::Group => "group",
+                    AclKind::Mask => "mask",
+                    AclKind::Other => "other",
+                },
+                self.qualifier.as_ref().map(|q| q.as_str()).unwrap_or(""))
+    }
+}
+
+impl fmt::Display for AclEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}{}{}",
+               self.subject(),
+               if self.perms & 4 == 4 { "r" } else { "-" },
+               if self.perms & 2 == 2 { "w" } else { "-" },
+               if self.perms & 1 == 1 { "x" } else { "-" })
+    }
+}
+
+impl FromStr for AclEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<AclEntry> {
+        let invalid = || Error::Generic(format!("Invalid ACL entry `{}`", s));
+        let mut parts: Vec<&str> = s.trim().split(':').collect();
+
+        let default = match parts.first() {
+            Some(&"d") | Some(&"default") => {
+                parts.remove(0);
+                true
+            },
+            _ => false,
+        };
+
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+
+        let kind = match parts[0] {
+            "u" | "user" => AclKind::User,
+            "g" | "group" => AclKind::Group,
+            "m" | "mask" => AclKind::Mask,
+            "o" | "other" => AclKind::Other,
+            _ => return Err(invalid()),
+        };
+
+        let qualifier = match parts[1] {
+            "" => None,
+            q => Some(q.to_string()),
+        };
+
+        let perms = if parts[2].len() == 1 && parts[2].chars().all(|c| c.is_digit(8)) {
+            parts[2].parse::<u8>().unwrap()
+        } else {
+            let mut p = 0;
+            for c in parts[2].chars() {
+                p |= match c {
+                    'r' => 4,
+                    'w' => 2,
+                    'x' => 1,
+                    '-' => 0,
+                    _ => return Err(invalid()),
+                };
+            }
+            p
+        };
+
+        Ok(AclEntry {
+            default: default,
+            kind: kind,
+            qualifier: qualifier,
+            perms: perms,
+        })
+    }
+}
+
+/// Check that none of `entries` are base entries for the owner, owning
+/// group or others, or the mask. These are part of the mode, so aren't
+/// returned by `file_get_acl` and would never match.
+fn check_not_base(entries: &[AclEntry]) -> Result<()> {
+    match entries.iter().find(|e| !e.default && (e.qualifier.is_none() || e.kind == AclKind::Mask)) {
+        Some(e) => Err(Error::Generic(format!("ACL entry `{}` is part of the mode, so use set_mode instead", e))),
+        None => Ok(()),
+    }
+}
+
+/// Add or update ACL entries, returning `true` if anything changed.
+pub fn ensure_acl(host: &mut Host, path: &Path, entries: &[AclEntry]) -> Result<bool> {
+    try!(check_not_base(entries));
+    let current = try!(Target::file_get_acl(host, path));
+    let changes: Vec<AclEntry> = entries.iter().filter(|e| !current.contains(e)).cloned().collect();
+
+    if changes.is_empty() {
+        Ok(false)
+    } else {
+        try!(Target::file_set_acl(host, path, &changes));
+        Ok(true)
+    }
+}
+
+/// Remove the ACL entries for the same subjects as `entries`,
+/// returning `true` if anything changed. Permissions are ignored.
+pub fn remove_acl(host: &mut Host, path: &Path, entries: &[AclEntry]) -> Result<bool> {
+    try!(check_not_base(entries));
+    let current = try!(Target::file_get_acl(host, path));
+    let changes: Vec<AclEntry> = entries.iter().filter(|e| current.iter().any(|c| c.same_subject(e))).cloned().collect();
+
+    if changes.is_empty() {
+        Ok(false)
+    } else {
+        try!(Target::file_remove_acl(host, path, &changes));
+        Ok(true)
+    }
+}
+
+/// Set an extended attribute, returning `true` if it changed.
+pub fn set_xattr(host: &mut Host, path: &Path, name: &str, value: &str) -> Result<bool> {
+    if try!(Target::file_get_xattr(host, path, name)).map_or(false, |v| v == value) {
+        Ok(false)
+    } else {
+        try!(Target::file_set_xattr(host, path, name, value));
+        Ok(true)
+    }
+}
+
+/// Remove an extended attribute, returning `true` if it existed.
+pub fn remove_xattr(host: &mut Host, path: &Path, name: &str) -> Result<bool> {
+    if try!(Target::file_get_xattr(host, path, name)).is_some() {
+        try!(Target::file_remove_xattr(host, path, name));
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Set an SELinux context, returning `true` if it changed.
+pub fn set_selinux_context(host: &mut Host, path: &Path, context: &str) -> Result<bool> {
+    if try!(Target::file_get_selinux_context(host, path)).map_or(false, |c| c == context) {
+        Ok(false)
+    } else {
+        try!(Target::file_set_selinux_context(host, path, context));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl_entry() {
+        let entry: AclEntry = "d:g:www-data:rx".parse().unwrap();
+        assert_eq!(entry, AclEntry::group("www-data", 5).into_default());
+        assert_eq!(entry.to_string(), "default:group:www-data:r-x");
+        assert_eq!(entry.subject(), "default:group:www-data");
+
+        assert_eq!("user:deploy:7".parse::<AclEntry>().unwrap(), AclEntry::user("deploy", 7));
+        assert_eq!("mask::rw-".parse::<AclEntry>().unwrap().to_string(), "mask::rw-");
+        assert!("user:deploy".parse::<AclEntry>().is_err());
+        assert!("user:deploy:rwz".parse::<AclEntry>().is_err());
+        assert!("world::r".parse::<AclEntry>().is_err());
+    }
+
+    #[test]
+    fn test_check_not_base() {
+        assert!(check_not_base(&[AclEntry::user("deploy", 7), "d:u::rwx".parse().unwrap(), "d:m::rx".parse().unwrap()]).is_ok());
+        assert!(check_not_base(&["user::rwx".parse().unwrap()]).is_err());
+        assert!(check_not_base(&["mask::r".parse().unwrap()]).is_err());
+        assert!(check_not_base(&["o::r".parse().unwrap()]).is_err());
+    }
+}
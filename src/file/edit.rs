@@ -0,0 +1,183 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Line and block level edits for text files.
+
+use error::{Error, Result};
+use regex::Regex;
+
+/// An idempotent edit to a text file.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum FileEdit {
+    /// Ensure a line is present. If `pattern` is set, the last line
+    /// matching it is replaced, otherwise the line is appended.
+    LinePresent {
+        line: String,
+        pattern: Option<String>,
+    },
+    /// Remove every line equal to `line`.
+    LineAbsent {
+        line: String,
+    },
+    /// Replace matches of `pattern` on each line with `replacement`.
+    /// The replacement may reference capture groups, e.g. `$1`.
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+    /// Ensure a block of lines is present between `# BEGIN <marker>`
+    /// and `# END <marker>`. An empty `content` removes the block.
+    Block {
+        marker: String,
+        content: String,
+    },
+}
+
+impl FileEdit {
+    /// Apply the edit to a file's contents, returning the new
+    /// contents if they changed.
+    ///
+    /// The file's line endings and trailing newline, or lack of one,
+    /// are preserved.
+    pub fn apply(&self, contents: &str) -> Result<Option<String>> {
+        let original: Vec<String> = contents.lines().map(|l| l.to_string()).collect();
+        let mut lines = original.clone();
+
+        match *self {
+            FileEdit::LinePresent { ref line, ref pattern } => {
+                if lines.contains(line) {
+                    return Ok(None);
+                }
+
+                let pos = match *pattern {
+                    Some(ref p) => {
+                        let regex = try!(Regex::new(p));
+                        lines.iter().rposition(|l| regex.is_match(l))
+                    },
+                    None => None,
+                };
+
+                match pos {
+                    Some(i) => lines[i] = line.clone(),
+                    None => lines.push(line.clone()),
+                }
+            },
+            FileEdit::LineAbsent { ref line } => {
+                if !lines.contains(line) {
+                    return Ok(None);
+                }
+
+                lines.retain(|l| l != line);
+            },
+            FileEdit::Replace { ref pattern, ref replacement } => {
+                let regex = try!(Regex::new(pattern));
+                lines = lines.iter().map(|l| regex.replace_all(l, replacement.as_str()).into_owned()).collect();
+            },
+            FileEdit::Block { ref marker, ref content } => {
+                let begin = format!("# BEGIN {}", marker);
+                let end = format!("# END {}", marker);
+
+                let mut block = Vec::new();
+                if !content.is_empty() {
+                    block.push(begin.clone());
+                    block.extend(content.lines().map(|l| l.to_string()));
+                    block.push(end.clone());
+                }
+
+                let start = lines.iter().position(|l| *l == begin);
+                let finish = start.and_then(|s| lines.iter().skip(s).position(|l| *l == end).map(|f| s + f));
+
+                match (start, finish) {
+                    (Some(s), Some(f)) => {
+                        let tail = lines.split_off(f + 1);
+                        lines.truncate(s);
+                        lines.extend(block);
+                        lines.extend(tail);
+                    },
+                    // Appending a new block would leave the dangling
+                    // BEGIN to swallow the lines after it next time.
+                    (Some(_), None) => return Err(Error::Generic(format!("Found `{}` without a matching `{}`", begin, end))),
+                    _ => lines.extend(block),
+                }
+            },
+        }
+
+        if lines == original {
+            return Ok(None);
+        }
+
+        let eol = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+        let mut edited = lines.join(eol);
+        if !edited.is_empty() && (contents.is_empty() || contents.ends_with('\n')) {
+            edited.push_str(eol);
+        }
+
+        Ok(Some(edited))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_present() {
+        let edit = FileEdit::LinePresent { line: "net.ipv4.ip_forward = 1".into(), pattern: None };
+        let contents = edit.apply("vm.swappiness = 10\n").unwrap().unwrap();
+        assert_eq!(contents, "vm.swappiness = 10\nnet.ipv4.ip_forward = 1\n");
+        assert!(edit.apply(&contents).unwrap().is_none());
+
+        let edit = FileEdit::LinePresent { line: "net.ipv4.ip_forward = 1".into(), pattern: Some("^net\\.ipv4\\.ip_forward".into()) };
+        let contents = edit.apply("net.ipv4.ip_forward = 0\nvm.swappiness = 10\n").unwrap().unwrap();
+        assert_eq!(contents, "net.ipv4.ip_forward = 1\nvm.swappiness = 10\n");
+    }
+
+    #[test]
+    fn test_line_absent() {
+        let edit = FileEdit::LineAbsent { line: "127.0.1.1 moo".into() };
+        let contents = edit.apply("127.0.0.1 localhost\n127.0.1.1 moo\n").unwrap().unwrap();
+        assert_eq!(contents, "127.0.0.1 localhost\n");
+        assert!(edit.apply(&contents).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replace() {
+        let edit = FileEdit::Replace { pattern: "^#?(PermitRootLogin) .*$".into(), replacement: "$1 no".into() };
+        let contents = edit.apply("Port 22\n#PermitRootLogin yes\n").unwrap().unwrap();
+        assert_eq!(contents, "Port 22\nPermitRootLogin no\n");
+        assert!(edit.apply(&contents).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_block() {
+        let edit = FileEdit::Block { marker: "intecture".into(), content: "10.0.0.1 web1\n10.0.0.2 web2".into() };
+        let contents = edit.apply("127.0.0.1 localhost\n").unwrap().unwrap();
+        assert_eq!(contents, "127.0.0.1 localhost\n# BEGIN intecture\n10.0.0.1 web1\n10.0.0.2 web2\n# END intecture\n");
+        assert!(edit.apply(&contents).unwrap().is_none());
+
+        let edit = FileEdit::Block { marker: "intecture".into(), content: "10.0.0.3 web3".into() };
+        let contents = edit.apply(&contents).unwrap().unwrap();
+        assert_eq!(contents, "127.0.0.1 localhost\n# BEGIN intecture\n10.0.0.3 web3\n# END intecture\n");
+
+        let edit = FileEdit::Block { marker: "intecture".into(), content: String::new() };
+        assert_eq!(edit.apply(&contents).unwrap().unwrap(), "127.0.0.1 localhost\n");
+
+        assert!(edit.apply("# BEGIN intecture\nmy line\n").is_err());
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let edit = FileEdit::Replace { pattern: "^nomatch$".into(), replacement: "x".into() };
+        assert!(edit.apply("Port 22").unwrap().is_none());
+        assert!(edit.apply("Port 22\r\nUsePAM yes\r\n").unwrap().is_none());
+
+        let edit = FileEdit::LinePresent { line: "UseDNS no".into(), pattern: None };
+        assert_eq!(edit.apply("Port 22").unwrap().unwrap(), "Port 22\nUseDNS no");
+        assert_eq!(edit.apply("Port 22\r\n").unwrap().unwrap(), "Port 22\r\nUseDNS no\r\n");
+    }
+}
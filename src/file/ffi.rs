@@ -130,6 +130,36 @@ pub extern "C" fn file_upload_file(file_ptr: *const File,
     0
 }
 
+#[no_mangle]
+pub extern "C" fn file_write_atomic(file_ptr: *const File,
+                                    host_ptr: *const Host,
+                                    local_path_ptr: *const c_char,
+                                    validate_ptr: *const c_char,
+                                    backup_ptr: *const c_char) -> int8_t {
+    let file = Leaky::new(tryrc!(readptr!(file_ptr, "File pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+    let local_path = tryrc!(ptrtostr!(local_path_ptr, "local path string"), -1);
+    let validate = if validate_ptr.is_null() { None } else { Some(tryrc!(ptrtostr!(validate_ptr, "validate string"), -1)) };
+    let backup = if backup_ptr.is_null() { None } else { Some(tryrc!(ptrtostr!(backup_ptr, "backup suffix string"), -1)) };
+
+    if tryrc!(file.write_atomic(&mut host, FileContent::Path(local_path.into()), validate, backup), -1) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn file_download(file_ptr: *const File, host_ptr: *const Host, local_path_ptr: *const c_char) -> uint8_t {
+    let file = Leaky::new(tryrc!(readptr!(file_ptr, "File pointer")));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer")));
+    let local_path = tryrc!(ptrtostr!(local_path_ptr, "local path string"));
+
+    tryrc!(file.download(&mut host, local_path));
+
+    0
+}
+
 #[no_mangle]
 pub extern "C" fn file_delete(file_ptr: *const File, host_ptr: *const Host) -> uint8_t {
     let file = Leaky::new(tryrc!(readptr!(file_ptr, "File pointer")));
@@ -206,6 +236,15 @@ pub extern "C" fn file_set_mode(file_ptr: *const File, host_ptr: *const Host, mo
     0
 }
 
+#[no_mangle]
+pub extern "C" fn file_checksum(file_ptr: *const File, host_ptr: *const Host) -> *mut c_char {
+    let file = Leaky::new(trynull!(readptr!(file_ptr, "File pointer")));
+    let mut host = Leaky::new(trynull!(readptr!(host_ptr, "Host pointer")));
+
+    let checksum = trynull!(file.checksum(&mut host));
+    trynull!(CString::new(checksum)).into_raw()
+}
+
 #[no_mangle]
 pub extern "C" fn file_free(file_ptr: *mut File) -> uint8_t {
     tryrc!(boxptr!(file_ptr, "File pointer"));
@@ -8,22 +8,36 @@
 
 //! File primitive.
 
+pub mod attr;
+mod edit;
 pub mod ffi;
 
+use command::Command;
 use error::Result;
+pub use self::attr::{AclEntry, AclKind};
+pub use self::edit::FileEdit;
 use host::Host;
 #[cfg(feature = "remote-run")]
 use host::HostSendRecv;
 use error::Error;
-#[cfg(feature = "remote-run")]
+use rustc_serialize::hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
 use std::fs;
+#[cfg(feature = "local-run")]
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use target::Target;
-#[cfg(feature = "remote-run")]
+use tempfile::{tempfile, NamedTempFile};
 use zfilexfer;
 
+/// Number of bytes requested from the host per chunk when reading a
+/// file.
+const CHUNK_SIZE: u64 = 1048576;
+
 /// Owner's user and group for a file.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct FileOwner {
     /// User name
     pub user_name: String,
@@ -35,6 +49,90 @@ pub struct FileOwner {
     pub group_gid: u64,
 }
 
+/// Type of a filesystem entry.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FileType {
+    /// Regular file
+    File,
+    /// Directory
+    Directory,
+    /// Symbolic link
+    Symlink,
+    /// Unix domain socket
+    Socket,
+    /// Named pipe
+    Fifo,
+    /// Block device
+    BlockDevice,
+    /// Character device
+    CharDevice,
+}
+
+/// Metadata for a filesystem entry. Symlinks are not followed, so the
+/// metadata describes the link itself.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileStat {
+    /// Type of entry
+    pub file_type: FileType,
+    /// Size in bytes
+    pub size: u64,
+    /// Last modification time, in seconds since the Unix epoch
+    pub mtime: i64,
+    /// Owner's user and group
+    pub owner: FileOwner,
+    /// Permissions mask, including the setuid, setgid and sticky
+    /// bits, e.g. `4755`
+    pub mode: u16,
+    /// Inode number
+    pub inode: u64,
+}
+
+/// Source of the desired contents for `File::ensure`.
+pub enum FileContent {
+    /// Path to a local file
+    Path(PathBuf),
+    /// Raw bytes
+    Bytes(Vec<u8>),
+    /// Open file handle, such as the output of `Template::render`
+    File(fs::File),
+}
+
+/// Desired state of a file for `File::ensure`.
+///
+/// Fields that are `None` are left untouched. If only one of `user`
+/// and `group` is set, the other is preserved.
+#[derive(Default)]
+pub struct FileSpec {
+    /// File contents
+    pub content: Option<FileContent>,
+    /// Owner's user name
+    pub user: Option<String>,
+    /// Owner's group name
+    pub group: Option<String>,
+    /// Permissions mask
+    pub mode: Option<u16>,
+    /// Suffix used to back up the existing file before its contents
+    /// are replaced
+    pub backup: Option<String>,
+    /// Command used to validate new contents before they replace the
+    /// file, e.g. `visudo -cf %s`. `%s` is replaced with the path of
+    /// the temporary file.
+    pub validate: Option<String>,
+}
+
+/// A change made to a file by `File::ensure`.
+#[derive(Debug, PartialEq)]
+pub enum FileChange {
+    /// File did not exist and was created
+    Created,
+    /// File contents were replaced
+    Content,
+    /// Owner was changed to the given user and group
+    Owner(String, String),
+    /// Permissions mask was changed to the given mode
+    Mode(u16),
+}
+
 /// Primitive for managing files.
 ///
 ///# Examples
@@ -92,16 +190,407 @@ impl File {
 
     #[cfg(feature = "remote-run")]
     /// Upload a file to the managed host.
-    pub fn upload<P: AsRef<Path>>(&self, host: &mut Host, local_path: P, options: Option<&[zfilexfer::FileOptions]>) -> Result<()> {
+    ///
+    /// The upload is skipped if the remote file's checksum matches
+    /// the local file. Returns `true` if the remote file was changed.
+    pub fn upload<P: AsRef<Path>>(&self, host: &mut Host, local_path: P, options: Option<&[zfilexfer::FileOptions]>) -> Result<bool> {
+        let mut fh = try!(fs::File::open(&local_path));
+        if !try!(self.is_changed(host, &mut fh)) {
+            return Ok(false);
+        }
+
         let mut file = try!(zfilexfer::File::open(&local_path, options));
-        host.send_fs_file(&mut file, &self.path)
+        try!(host.send_fs_file(&mut file, &self.path));
+        Ok(true)
     }
 
     #[cfg(feature = "remote-run")]
     /// Upload a file handle to the managed host.
-    pub fn upload_file(&self, host: &mut Host, file: fs::File, options: Option<&[zfilexfer::FileOptions]>) -> Result<()> {
+    ///
+    /// The upload is skipped if the remote file's checksum matches
+    /// the file handle. Returns `true` if the remote file was changed.
+    pub fn upload_file(&self, host: &mut Host, mut file: fs::File, options: Option<&[zfilexfer::FileOptions]>) -> Result<bool> {
+        if !try!(self.is_changed(host, &mut file)) {
+            return Ok(false);
+        }
+
+        // Reset cursor to beginning of file for sending
+        try!(file.seek(SeekFrom::Start(0)));
+
         let mut zfile = try!(zfilexfer::File::open_file(file, options));
-        host.send_fs_file(&mut zfile, &self.path)
+        try!(host.send_fs_file(&mut zfile, &self.path));
+        Ok(true)
+    }
+
+    #[cfg(feature = "local-run")]
+    /// Copy a local file into place.
+    ///
+    /// The copy is skipped if the destination's checksum matches the
+    /// local file. Returns `true` if the destination was changed.
+    pub fn upload<P: AsRef<Path>>(&self, host: &mut Host, local_path: P, options: Option<&[zfilexfer::FileOptions]>) -> Result<bool> {
+        let fh = try!(fs::File::open(&local_path));
+        self.upload_file(host, fh, options)
+    }
+
+    #[cfg(feature = "local-run")]
+    /// Copy a file handle into place.
+    ///
+    /// The copy is skipped if the destination's checksum matches the
+    /// file handle. Returns `true` if the destination was changed.
+    pub fn upload_file(&self, host: &mut Host, mut file: fs::File, options: Option<&[zfilexfer::FileOptions]>) -> Result<bool> {
+        if !try!(self.is_changed(host, &mut file)) {
+            return Ok(false);
+        }
+
+        if try!(self.exists(host)) {
+            for opt in options.unwrap_or(&[]) {
+                if let zfilexfer::FileOptions::BackupExisting(ref suffix) = *opt {
+                    let mut backup = self.path.clone().into_os_string();
+                    backup.push(suffix);
+                    try!(fs::copy(&self.path, backup));
+                }
+            }
+        }
+
+        // Reset cursor to beginning of file for copying
+        try!(file.seek(SeekFrom::Start(0)));
+
+        // Truncating the existing file preserves its owner and mode
+        let mut dest = try!(fs::File::create(&self.path));
+        try!(io::copy(&mut file, &mut dest));
+        Ok(true)
+    }
+
+    /// Atomically replace the file's contents.
+    ///
+    /// The new contents are written to a temporary file alongside the
+    /// destination, which is checked with the `validate` command (if
+    /// any) and then renamed into place. An existing file's owner,
+    /// mode, ACLs and SELinux context are carried over, and it is
+    /// copied to `backup` (a path suffix) first if given. Any other
+    /// extended attributes are lost. A new file gets the default mode
+    /// for the host's umask. If any step fails, the temporary file is
+    /// removed and the original is left untouched.
+    ///
+    /// The write is skipped if the checksums already match. Returns
+    /// `true` if the file was changed.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{File, FileContent, Host};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let file = File::new(&mut host, "/etc/sudoers.d/deploy").unwrap();
+    /// file.write_atomic(&mut host,
+    ///                   FileContent::Bytes(b"deploy ALL=(ALL) NOPASSWD: ALL\n".to_vec()),
+    ///                   Some("visudo -cf %s"),
+    ///                   None).unwrap();
+    /// ```
+    pub fn write_atomic(&self, host: &mut Host, content: FileContent, validate: Option<&str>, backup: Option<&str>) -> Result<bool> {
+        self.write_atomic_mode(host, content, validate, backup, None)
+    }
+
+    /// Same as `write_atomic`, except a new file is created with
+    /// `mode` instead of the umask default.
+    fn write_atomic_mode(&self, host: &mut Host, content: FileContent, validate: Option<&str>, backup: Option<&str>, mode: Option<u16>) -> Result<bool> {
+        let mut fh = match content {
+            FileContent::Path(path) => try!(fs::File::open(path)),
+            FileContent::Bytes(bytes) => {
+                let mut fh = try!(tempfile());
+                try!(fh.write_all(&bytes));
+                fh
+            },
+            FileContent::File(fh) => fh,
+        };
+
+        if !try!(self.is_changed(host, &mut fh)) {
+            return Ok(false);
+        }
+
+        if let Some(cmd) = validate {
+            if !cmd.contains("%s") {
+                return Err(Error::Generic(format!("Validation command `{}` must contain %s", cmd)));
+            }
+        }
+
+        let tmp = try!(self.tmp_path());
+
+        // Reset cursor to beginning of file for sending
+        try!(fh.seek(SeekFrom::Start(0)));
+        try!(self.send_tmp(host, fh, &tmp));
+
+        match self.replace_with(host, &tmp, validate, backup, mode) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                let _ = Target::file_delete(host, &tmp);
+                Err(e)
+            }
+        }
+    }
+
+    /// Get a path alongside the file for its new contents. The name is
+    /// random so that it can't be planted in advance, e.g. as a
+    /// symlink, and concurrent writes don't share a temporary file.
+    fn tmp_path(&self) -> Result<PathBuf> {
+        let mut rand = [0; 8];
+        try!(try!(fs::File::open("/dev/urandom")).read_exact(&mut rand));
+
+        let mut name = OsString::from(".");
+        name.push(self.path.file_name().unwrap_or(self.path.as_os_str()));
+        name.push(format!(".inapi-{}", rand[..].to_hex()));
+        Ok(self.path.with_file_name(name))
+    }
+
+    #[cfg(feature = "remote-run")]
+    fn send_tmp(&self, host: &mut Host, file: fs::File, tmp: &Path) -> Result<()> {
+        let mut zfile = try!(zfilexfer::File::open_file(file, None));
+        if let Err(e) = host.send_fs_file(&mut zfile, tmp) {
+            let _ = Target::file_delete(host, tmp);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "local-run")]
+    #[allow(unused_variables)]
+    fn send_tmp(&self, host: &mut Host, mut file: fs::File, tmp: &Path) -> Result<()> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        // Refuse to follow or reuse anything already at the path
+        let mut dest = try!(fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(tmp));
+        let result = io::copy(&mut file, &mut dest).and_then(|_| dest.sync_all());
+        if let Err(e) = result {
+            let _ = fs::remove_file(tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn replace_with(&self, host: &mut Host, tmp: &Path, validate: Option<&str>, backup: Option<&str>, mode: Option<u16>) -> Result<()> {
+        if let Some(cmd) = validate {
+            let quoted = format!("'{}'", tmp.to_string_lossy().replace("'", "'\\''"));
+            let result = try!(Command::new(&cmd.replace("%s", &quoted)).exec(host));
+            if result.exit_code != 0 {
+                return Err(Error::Generic(format!("Validation failed for {}: {}", self.path.display(), result.stderr.trim())));
+            }
+        }
+
+        if try!(self.exists(host)) {
+            let owner = try!(self.get_owner(host));
+            try!(Target::file_set_owner(host, tmp, &owner.user_name, &owner.group_name));
+            let mode = try!(self.get_mode(host));
+            try!(Target::file_set_mode(host, tmp, mode));
+
+            // Targets without ACL or SELinux support fail to read
+            // them, in which case there is nothing to carry over.
+            if let Ok(entries) = Target::file_get_acl(host, self.path.as_path()) {
+                if !entries.is_empty() {
+                    try!(Target::file_set_acl(host, tmp, &entries));
+                }
+            }
+
+            if let Ok(Some(context)) = Target::file_get_selinux_context(host, self.path.as_path()) {
+                try!(Target::file_set_selinux_context(host, tmp, &context));
+            }
+
+            if let Some(suffix) = backup {
+                let mut path = self.path.clone().into_os_string();
+                path.push(suffix);
+                try!(Target::file_copy(host, self.path.as_path(), Path::new(&path)));
+            }
+        } else {
+            // The temporary file is private until it's validated
+            let mode = match mode {
+                Some(mode) => mode,
+                None => try!(default_mode(host)),
+            };
+            try!(Target::file_set_mode(host, tmp, mode));
+        }
+
+        Target::file_mv(host, tmp, self.path.as_path())
+    }
+
+    fn is_changed(&self, host: &mut Host, local: &mut fs::File) -> Result<bool> {
+        if !try!(self.exists(host)) {
+            return Ok(true);
+        }
+
+        try!(local.seek(SeekFrom::Start(0)));
+        Ok(try!(checksum_reader(local)) != try!(self.checksum(host)))
+    }
+
+    /// Converge the file to the desired state, only changing the
+    /// attributes that differ from `spec`.
+    ///
+    /// New contents are written with `File::write_atomic`, so the file
+    /// is only replaced once they pass `spec.validate`.
+    ///
+    /// Returns each change that was made, which is empty if the file
+    /// was already in the desired state.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{File, FileContent, FileSpec, Host, MapBuilder, Template};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let template = Template::new("/path/to/template").unwrap();
+    /// let data = MapBuilder::new().insert_str("name", "Cyril Figgis").build();
+    ///
+    /// let file = File::new(&mut host, "/etc/app.conf").unwrap();
+    /// let changes = file.ensure(&mut host, FileSpec {
+    ///     content: Some(FileContent::File(template.render_data(&data).unwrap())),
+    ///     user: Some("root".into()),
+    ///     mode: Some(644),
+    ///     backup: Some("_bk".into()),
+    ///     validate: Some("app --check-config %s".into()),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// ```
+    pub fn ensure(&self, host: &mut Host, spec: FileSpec) -> Result<Vec<FileChange>> {
+        let mut changes = Vec::new();
+        let exists = try!(self.exists(host));
+
+        match spec.content {
+            Some(content) => {
+                let changed = try!(self.write_atomic_mode(host,
+                                                          content,
+                                                          spec.validate.as_ref().map(|v| v.as_str()),
+                                                          spec.backup.as_ref().map(|b| b.as_str()),
+                                                          spec.mode));
+
+                if changed {
+                    changes.push(if exists { FileChange::Content } else { FileChange::Created });
+                }
+            },
+            None if !exists => return Err(Error::Generic("File does not exist and no content was provided".into())),
+            None => (),
+        }
+
+        if spec.user.is_some() || spec.group.is_some() {
+            let owner = try!(self.get_owner(host));
+            let user = spec.user.unwrap_or(owner.user_name.clone());
+            let group = spec.group.unwrap_or(owner.group_name.clone());
+
+            if user != owner.user_name || group != owner.group_name {
+                try!(self.set_owner(host, &user, &group));
+                changes.push(FileChange::Owner(user, group));
+            }
+        }
+
+        if let Some(mode) = spec.mode {
+            if try!(self.get_mode(host)) != mode {
+                try!(self.set_mode(host, mode));
+                changes.push(FileChange::Mode(mode));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Download the file from the managed host to a local path.
+    ///
+    /// The file is fetched in chunks into a temporary file alongside
+    /// `local_path`, which only replaces it once the host's checksum
+    /// matches. If the download fails, any existing local copy is left
+    /// untouched. A replaced file keeps its permissions, and a new one
+    /// is only readable by the current user.
+    pub fn download<P: AsRef<Path>>(&self, host: &mut Host, local_path: P) -> Result<()> {
+        let local_path = local_path.as_ref();
+        let dir = match local_path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+
+        // The temporary file is removed when dropped, e.g. on error
+        let mut tmp = try!(NamedTempFile::new_in(dir));
+        try!(self.read_into(host, &mut tmp));
+
+        if let Ok(meta) = fs::metadata(local_path) {
+            try!(fs::set_permissions(tmp.path(), meta.permissions()));
+        }
+
+        try!(tmp.persist(local_path).map_err(|e| e.error));
+        Ok(())
+    }
+
+    /// Read the file's contents into a string.
+    pub fn read_to_string(&self, host: &mut Host) -> Result<String> {
+        let mut buf = Vec::new();
+        try!(self.read_into(host, &mut buf));
+        String::from_utf8(buf).or(Err(Error::Generic("File contents are not valid UTF-8".into())))
+    }
+
+    fn read_into<W: Write>(&self, host: &mut Host, writer: &mut W) -> Result<()> {
+        let mut hasher = Sha256::default();
+        let mut offset = 0;
+
+        loop {
+            let chunk = try!(Target::file_read(host, &self.path, offset, CHUNK_SIZE));
+            if chunk.is_empty() {
+                break;
+            }
+
+            hasher.input(&chunk);
+            try!(writer.write_all(&chunk));
+            offset += chunk.len() as u64;
+        }
+
+        if hasher.result().as_slice().to_hex() != try!(self.checksum(host)) {
+            return Err(Error::Generic("File checksum does not match the host's copy".into()));
+        }
+
+        Ok(())
+    }
+
+    /// Apply an edit to the file's contents. Returns `true` if the
+    /// file was changed.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{File, FileEdit, Host};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let file = File::new(&mut host, "/etc/sysctl.conf").unwrap();
+    /// file.edit(&mut host, &FileEdit::LinePresent {
+    ///     line: "net.ipv4.ip_forward = 1".into(),
+    ///     pattern: Some("^net\\.ipv4\\.ip_forward\\b".into()),
+    /// }).unwrap();
+    /// ```
+    pub fn edit(&self, host: &mut Host, edit: &FileEdit) -> Result<bool> {
+        Target::file_edit(host, &self.path, edit)
+    }
+
+    /// Ensure a line is present in the file, appending it if missing.
+    pub fn ensure_line(&self, host: &mut Host, line: &str) -> Result<bool> {
+        self.edit(host, &FileEdit::LinePresent { line: line.into(), pattern: None })
+    }
+
+    /// Remove every occurrence of a line from the file.
+    pub fn remove_line(&self, host: &mut Host, line: &str) -> Result<bool> {
+        self.edit(host, &FileEdit::LineAbsent { line: line.into() })
+    }
+
+    /// Replace regex matches on each line of the file.
+    pub fn replace(&self, host: &mut Host, pattern: &str, replacement: &str) -> Result<bool> {
+        self.edit(host, &FileEdit::Replace { pattern: pattern.into(), replacement: replacement.into() })
+    }
+
+    /// Insert or replace a block of lines delimited by
+    /// `# BEGIN <marker>` and `# END <marker>`.
+    pub fn ensure_block(&self, host: &mut Host, marker: &str, content: &str) -> Result<bool> {
+        self.edit(host, &FileEdit::Block { marker: marker.into(), content: content.into() })
+    }
+
+    /// Get the SHA-256 checksum of the file's contents as a hex
+    /// string.
+    pub fn checksum(&self, host: &mut Host) -> Result<String> {
+        Target::file_checksum(host, &self.path)
     }
 
     /// Delete the file.
@@ -142,6 +631,109 @@ impl File {
     pub fn set_mode(&self, host: &mut Host, mode: u16) -> Result<()> {
         Target::file_set_mode(host, &self.path, mode)
     }
+
+    /// Get the file's metadata.
+    pub fn stat(&self, host: &mut Host) -> Result<FileStat> {
+        Target::file_stat(host, &self.path)
+    }
+
+    /// Get the file's extended POSIX ACL entries. The base entries
+    /// for the owner, group and others are represented by the mode,
+    /// so they are not included.
+    pub fn get_acl(&self, host: &mut Host) -> Result<Vec<AclEntry>> {
+        Target::file_get_acl(host, &self.path)
+    }
+
+    /// Add or update ACL entries. Returns `true` if the ACL changed.
+    ///
+    /// Base entries and the mask are part of the mode, so passing them
+    /// is an error. Use `File::set_mode` instead.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{AclEntry, File, Host};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let file = File::new(&mut host, "/etc/myapp.conf").unwrap();
+    /// file.ensure_acl(&mut host, &[
+    ///     AclEntry::user("deploy", 6),
+    ///     "group:auditors:r--".parse().unwrap(),
+    /// ]).unwrap();
+    /// ```
+    pub fn ensure_acl(&self, host: &mut Host, entries: &[AclEntry]) -> Result<bool> {
+        attr::ensure_acl(host, &self.path, entries)
+    }
+
+    /// Remove the ACL entries for the given users and groups,
+    /// ignoring permissions. Returns `true` if the ACL changed.
+    pub fn remove_acl(&self, host: &mut Host, entries: &[AclEntry]) -> Result<bool> {
+        attr::remove_acl(host, &self.path, entries)
+    }
+
+    /// Get an extended attribute, e.g. `user.checksum`.
+    pub fn get_xattr(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        Target::file_get_xattr(host, &self.path, name)
+    }
+
+    /// Set an extended attribute. Returns `true` if it changed.
+    pub fn set_xattr(&self, host: &mut Host, name: &str, value: &str) -> Result<bool> {
+        attr::set_xattr(host, &self.path, name, value)
+    }
+
+    /// Remove an extended attribute. Returns `true` if it existed.
+    pub fn remove_xattr(&self, host: &mut Host, name: &str) -> Result<bool> {
+        attr::remove_xattr(host, &self.path, name)
+    }
+
+    /// Get the file's SELinux context, or `None` if it has none.
+    pub fn get_selinux_context(&self, host: &mut Host) -> Result<Option<String>> {
+        Target::file_get_selinux_context(host, &self.path)
+    }
+
+    /// Set the file's SELinux context, e.g.
+    /// `system_u:object_r:httpd_sys_content_t:s0`. Returns `true` if
+    /// it changed.
+    pub fn set_selinux_context(&self, host: &mut Host, context: &str) -> Result<bool> {
+        attr::set_selinux_context(host, &self.path, context)
+    }
+
+    /// Reset the file's SELinux context to the policy default, like
+    /// `restorecon`. Returns `true` if it changed.
+    pub fn restore_selinux_context(&self, host: &mut Host) -> Result<bool> {
+        Target::file_restore_selinux_context(host, &self.path)
+    }
+}
+
+/// Calculate the SHA-256 checksum of a reader's contents as a hex
+/// string.
+pub fn checksum_reader<R: Read>(reader: &mut R) -> Result<String> {
+    let mut hasher = Sha256::default();
+    let mut buf = [0; 8192];
+
+    loop {
+        let len = try!(reader.read(&mut buf));
+        if len == 0 {
+            break;
+        }
+        hasher.input(&buf[..len]);
+    }
+
+    Ok(hasher.result().as_slice().to_hex())
+}
+
+/// Get the mode a new file receives on the host, i.e. 666 less the
+/// umask.
+fn default_mode(host: &mut Host) -> Result<u16> {
+    let result = try!(Command::new("umask").exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    let umask = try!(u16::from_str_radix(result.stdout.trim(), 8)
+                         .or(Err(Error::Generic(format!("Invalid umask `{}`", result.stdout.trim())))));
+    format!("{:o}", 0o666 & !umask).parse().or(Err(Error::Generic("Could not convert umask to a mode".into())))
 }
 
 pub trait FileTarget<P: AsRef<Path>> {
@@ -154,6 +746,19 @@ pub trait FileTarget<P: AsRef<Path>> {
     fn file_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()>;
     fn file_get_mode(host: &mut Host, path: P) -> Result<u16>;
     fn file_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()>;
+    fn file_checksum(host: &mut Host, path: P) -> Result<String>;
+    fn file_read(host: &mut Host, path: P, offset: u64, len: u64) -> Result<Vec<u8>>;
+    fn file_edit(host: &mut Host, path: P, edit: &FileEdit) -> Result<bool>;
+    fn file_stat(host: &mut Host, path: P) -> Result<FileStat>;
+    fn file_get_acl(host: &mut Host, path: P) -> Result<Vec<AclEntry>>;
+    fn file_set_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()>;
+    fn file_remove_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()>;
+    fn file_get_xattr(host: &mut Host, path: P, name: &str) -> Result<Option<String>>;
+    fn file_set_xattr(host: &mut Host, path: P, name: &str, value: &str) -> Result<()>;
+    fn file_remove_xattr(host: &mut Host, path: P, name: &str) -> Result<()>;
+    fn file_get_selinux_context(host: &mut Host, path: P) -> Result<Option<String>>;
+    fn file_set_selinux_context(host: &mut Host, path: P, context: &str) -> Result<()>;
+    fn file_restore_selinux_context(host: &mut Host, path: P) -> Result<bool>;
 }
 
 #[cfg(test)]
@@ -161,9 +766,20 @@ mod tests {
     #[cfg(feature = "remote-run")]
     use czmq::{ZMsg, ZSys};
     use host::Host;
+    use std::fs;
+    #[cfg(feature = "local-run")]
+    use std::os::unix::fs::PermissionsExt;
+    use std::io::Write;
     #[cfg(feature = "remote-run")]
     use std::thread;
     use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_checksum_reader() {
+        let mut data: &[u8] = b"moo";
+        assert_eq!(checksum_reader(&mut data).unwrap(), "47dfae9288abf3d5d2252abfb0bd6ac9662637d646e6df9d5d274bc336e27abc");
+    }
 
     #[cfg(feature = "local-run")]
     #[test]
@@ -486,4 +1102,412 @@ mod tests {
 
         agent_mock.join().unwrap();
     }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_checksum() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::checksum", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("abc123").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        assert_eq!(file.checksum(&mut host).unwrap(), "abc123");
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_upload_unchanged() {
+        ZSys::init();
+
+        let tempdir = TempDir::new("file_test_upload_unchanged").unwrap();
+        let local_path = format!("{}/local", tempdir.path().to_str().unwrap());
+        let mut fh = fs::File::create(&local_path).unwrap();
+        fh.write_all(b"moo").unwrap();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::exists", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::checksum", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("47dfae9288abf3d5d2252abfb0bd6ac9662637d646e6df9d5d274bc336e27abc").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        // No file socket, as an unchanged file should never be sent
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        assert!(!file.upload(&mut host, &local_path, None).unwrap());
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_write_atomic() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let dir = TempDir::new("test_file_write_atomic").unwrap();
+        let path = dir.path().join("app.conf");
+        fs::File::create(&path).unwrap().write_all(b"old\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let file = File::new(&mut host, &path).unwrap();
+        assert!(file.write_atomic(&mut host, FileContent::Bytes(b"bad\n".to_vec()), Some("grep -q good %s"), None).is_err());
+        assert_eq!(file.read_to_string(&mut host).unwrap(), "old\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(file.write_atomic(&mut host, FileContent::Bytes(b"good\n".to_vec()), Some("grep -q good %s"), Some("_bk")).unwrap());
+        assert_eq!(file.read_to_string(&mut host).unwrap(), "good\n");
+        let backup = File::new(&mut host, dir.path().join("app.conf_bk")).unwrap();
+        assert_eq!(backup.read_to_string(&mut host).unwrap(), "old\n");
+        assert_eq!(file.get_mode(&mut host).unwrap(), 640);
+
+        assert!(!file.write_atomic(&mut host, FileContent::Bytes(b"good\n".to_vec()), None, None).unwrap());
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_write_atomic_new() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let dir = TempDir::new("test_file_write_atomic_new").unwrap();
+        fs::File::create(dir.path().join("control")).unwrap();
+        let control = File::new(&mut host, dir.path().join("control")).unwrap();
+
+        let file = File::new(&mut host, dir.path().join("app.conf")).unwrap();
+        assert!(file.write_atomic(&mut host, FileContent::Bytes(b"new\n".to_vec()), None, None).unwrap());
+        assert_eq!(file.get_mode(&mut host).unwrap(), control.get_mode(&mut host).unwrap());
+
+        let file = File::new(&mut host, dir.path().join("app2.conf")).unwrap();
+        let changes = file.ensure(&mut host, FileSpec {
+            content: Some(FileContent::Bytes(b"new\n".to_vec())),
+            mode: Some(640),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(changes, vec![FileChange::Created]);
+        assert_eq!(file.get_mode(&mut host).unwrap(), 640);
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_ensure() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            for _ in 0..2 {
+                let msg = ZMsg::recv(&mut server).unwrap();
+                assert_eq!("file::exists", msg.popstr().unwrap().unwrap());
+                assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+                let reply = ZMsg::new();
+                reply.addstr("Ok").unwrap();
+                reply.addstr("1").unwrap();
+                reply.send(&mut server).unwrap();
+            }
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::checksum", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("47dfae9288abf3d5d2252abfb0bd6ac9662637d646e6df9d5d274bc336e27abc").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::get_owner", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("root").unwrap();
+            reply.addstr("0").unwrap();
+            reply.addstr("group").unwrap();
+            reply.addstr("123").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::set_owner", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+            assert_eq!("user", msg.popstr().unwrap().unwrap());
+            assert_eq!("group", msg.popstr().unwrap().unwrap());
+
+            server.send_str("Ok").unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::get_mode", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("644").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        let changes = file.ensure(&mut host, FileSpec {
+            content: Some(FileContent::Bytes(b"moo".to_vec())),
+            user: Some("user".into()),
+            mode: Some(644),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(changes, vec![FileChange::Owner("user".into(), "group".into())]);
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_read_to_string() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            for &(offset, chunk) in &[("0", "moo"), ("3", "")] {
+                let msg = ZMsg::recv(&mut server).unwrap();
+                assert_eq!("file::read", msg.popstr().unwrap().unwrap());
+                assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+                assert_eq!(offset, msg.popstr().unwrap().unwrap());
+                assert_eq!("1048576", msg.popstr().unwrap().unwrap());
+
+                let reply = ZMsg::new();
+                reply.addstr("Ok").unwrap();
+                reply.addstr(chunk).unwrap();
+                reply.send(&mut server).unwrap();
+            }
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::checksum", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("47dfae9288abf3d5d2252abfb0bd6ac9662637d646e6df9d5d274bc336e27abc").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        assert_eq!(file.read_to_string(&mut host).unwrap(), "moo");
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_download() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let dir = TempDir::new("test_file_download").unwrap();
+        let remote = dir.path().join("remote");
+        fs::File::create(&remote).unwrap().write_all(b"new\n").unwrap();
+        let local = dir.path().join("local");
+        fs::File::create(&local).unwrap().write_all(b"old\n").unwrap();
+        fs::set_permissions(&local, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let file = File::new(&mut host, &remote).unwrap();
+        file.download(&mut host, &local).unwrap();
+        let downloaded = File::new(&mut host, &local).unwrap();
+        assert_eq!(downloaded.read_to_string(&mut host).unwrap(), "new\n");
+        assert_eq!(downloaded.get_mode(&mut host).unwrap(), 640);
+
+        // A failed download keeps the existing copy
+        fs::remove_file(&remote).unwrap();
+        assert!(file.download(&mut host, &local).is_err());
+        assert_eq!(downloaded.read_to_string(&mut host).unwrap(), "new\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_edit() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::edit", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+            assert_eq!(r#"{"LineAbsent":{"line":"moo"}}"#, msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        assert!(file.remove_line(&mut host, "moo").unwrap());
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_stat() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::stat", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("{\"file_type\":\"File\",\"size\":3,\"mtime\":1500000000,\"owner\":{\"user_name\":\"root\",\"user_uid\":0,\"group_name\":\"wheel\",\"group_gid\":0},\"mode\":4755,\"inode\":42}").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        let stat = file.stat(&mut host).unwrap();
+        assert_eq!(stat.file_type, FileType::File);
+        assert_eq!(stat.mode, 4755);
+        assert_eq!(stat.owner.group_name, "wheel");
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_ensure_acl() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::is_file", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::get_acl", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("[{\"default\":false,\"kind\":\"User\",\"qualifier\":\"deploy\",\"perms\":6}]").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("file::set_acl", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/test", msg.popstr().unwrap().unwrap());
+            assert_eq!("[{\"default\":false,\"kind\":\"Group\",\"qualifier\":\"auditors\",\"perms\":4}]", msg.popstr().unwrap().unwrap());
+
+            server.send_str("Ok").unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let file = File::new(&mut host, "/tmp/test").unwrap();
+        assert!(file.ensure_acl(&mut host, &[
+            AclEntry::user("deploy", 6),
+            AclEntry::group("auditors", 4),
+        ]).unwrap());
+
+        agent_mock.join().unwrap();
+    }
 }
@@ -11,12 +11,51 @@
 #[macro_use]
 mod macros;
 mod condition;
+mod schema;
+
+pub use self::schema::SchemaViolation;
 
 use error::{Error, Result};
+use glob::glob;
+use regex::{Captures, Regex};
+use serde::Deserialize;
 use serde_json::{self, Value, Map};
-use std::fs;
+use std::{env, fs};
 use std::path::{Path, PathBuf};
 
+/// Deserialize data into your own types.
+///
+///# Examples
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate inapi;
+/// # use inapi::{DataAs, Value};
+/// #[derive(Deserialize)]
+/// struct Nginx {
+///     port: u16,
+///     vhosts: Vec<String>,
+/// }
+///
+/// # fn main() {
+/// # let data = Value::Null;
+/// let nginx: Nginx = data.data_as("/nginx").unwrap();
+/// # }
+/// ```
+pub trait DataAs {
+    /// Deserialize the value at a JSON pointer into `T`. Use an
+    /// empty pointer (`""`) to deserialize the whole document.
+    fn data_as<T: Deserialize>(&self, pointer: &str) -> Result<T>;
+}
+
+impl DataAs for Value {
+    fn data_as<T: Deserialize>(&self, pointer: &str) -> Result<T> {
+        let value = try!(self.pointer(pointer).ok_or(Error::Generic(format!("Could not find {} in data", pointer))));
+        serde_json::from_value(value.clone())
+            .map_err(|e| Error::Generic(format!("Could not deserialize {} in data: {}", pointer, e)))
+    }
+}
+
 #[doc(hidden)]
 pub fn open<P: AsRef<Path>>(path: P) -> Result<Value> {
     let mut p = PathBuf::from("data");
@@ -35,13 +74,64 @@ fn open_raw<P: AsRef<Path>>(path: P) -> Result<Value> {
     }
 }
 
-pub fn merge(mut me: Value, mut last_value: Value) -> Result<Value> {
+pub fn merge(me: Value, last_value: Value) -> Result<Value> {
+    let mut data = try!(merge_deps(me, last_value));
+    strip_directives(&mut data);
+    interpolate(data)
+}
+
+fn merge_deps(mut me: Value, mut last_value: Value) -> Result<Value> {
     for dep in try!(dependencies(&mut me)) {
-        last_value = try!(merge(dep, last_value));
+        last_value = try!(merge_deps(dep, last_value));
     }
 
     let lv_clone = last_value.clone();
-    Ok(try!(merge_values(me, last_value, &lv_clone)))
+    Ok(try!(merge_values(me, last_value, &MergeStrategy::Append, &lv_clone)))
+}
+
+/// Validate data against the schemas shipped by each payload listed
+/// in `_payloads`, i.e. `payloads/<name>/data/schema.json`.
+///
+/// All violations are collected and returned in a single error.
+pub fn validate(data: &Value) -> Result<()> {
+    validate_in(Path::new(""), data)
+}
+
+/// Validate data against payload schemas, looking for the `payloads`
+/// directory inside `base`.
+fn validate_in(base: &Path, data: &Value) -> Result<()> {
+    let mut violations = Vec::new();
+    let mut checked: Vec<&str> = Vec::new();
+
+    if let Some(payloads) = data.get("_payloads").and_then(|p| p.as_array()) {
+        for p in payloads {
+            let name = match p.as_str() {
+                Some(s) => s.split("::").next().unwrap(),
+                None => return Err(Error::Generic("Non-string value in `_payloads`".into())),
+            };
+
+            if checked.contains(&name) {
+                continue;
+            }
+            checked.push(name);
+
+            let mut buf = base.join("payloads");
+            buf.push(name);
+            buf.push("data");
+            buf.push("schema.json");
+
+            if buf.is_file() {
+                let schema = try!(open_raw(&buf));
+                violations.append(&mut try!(schema::validate(&schema, data)));
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Schema(violations))
+    }
 }
 
 fn dependencies(me: &mut Value) -> Result<Vec<Value>> {
@@ -72,7 +162,9 @@ fn dependencies(me: &mut Value) -> Result<Vec<Value>> {
                     }
                     payloads.insert(0, payload.into());
                 } else {
-                    deps.push(try!(open(s)));
+                    for p in try!(expand_include(s)).into_iter().rev() {
+                        deps.push(try!(open_raw(&p)));
+                    }
                 }
             } else {
                 return Err(Error::Generic("Non-string value in `_include`".into()));
@@ -87,30 +179,231 @@ fn dependencies(me: &mut Value) -> Result<Vec<Value>> {
     Ok(deps)
 }
 
-fn merge_values(into: Value, mut from: Value, parent_from: &Value) -> Result<Value> {
+/// Resolve an `_include` entry to a list of data files, in order of
+/// importance. Entries may be:
+///
+/// - a single file, e.g. `hosts/web.json`
+/// - a glob pattern, e.g. `roles/*.json`
+/// - a directory, which includes every `.json` file inside it
+///
+/// Matches are sorted by path, so `roles/a.json` is more important
+/// than `roles/b.json`, as if they had been listed individually.
+///
+/// Environment variables are expanded using the `${VAR}` syntax, and
+/// entries prefixed with `optional:` are skipped if they don't exist.
+fn expand_include(include: &str) -> Result<Vec<PathBuf>> {
+    expand_include_in(Path::new(""), include)
+}
+
+/// Resolve an `_include` entry, looking for relative paths in the
+/// `data` directory inside `base`.
+fn expand_include_in(base: &Path, include: &str) -> Result<Vec<PathBuf>> {
+    let (optional, include) = if include.starts_with("optional:") {
+        let (_, i) = include.split_at(9);
+        (true, i.trim())
+    } else {
+        (false, include)
+    };
+
+    let include = match expand_env(include) {
+        Ok(i) => i,
+        Err(_) if optional => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut path = base.join("data");
+    path.push(&include);
+
+    let mut paths = Vec::new();
+
+    if include.contains(|c: char| c == '*' || c == '?' || c == '[') {
+        let pattern = try!(path.to_str().ok_or(Error::Generic(format!("Invalid path in `_include`: {}", include))));
+        let matches = try!(glob(pattern).map_err(|e| Error::Generic(format!("Invalid pattern `{}` in `_include`: {}", include, e))));
+
+        for m in matches {
+            let p = try!(m.map_err(|e| Error::Generic(format!("Could not read `{}` in `_include`: {}", include, e))));
+            if p.is_file() {
+                paths.push(p);
+            }
+        }
+
+        if paths.is_empty() && !optional {
+            return Err(Error::Generic(format!("No files matched `{}` in `_include`", include)));
+        }
+    }
+    else if path.is_dir() {
+        for entry in try!(fs::read_dir(&path)) {
+            let p = try!(entry).path();
+            if p.is_file() && p.extension().map_or(false, |e| e == "json") {
+                paths.push(p);
+            }
+        }
+
+        paths.sort();
+    }
+    else if !optional || path.exists() {
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+fn expand_env(s: &str) -> Result<String> {
+    let re = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+    let mut err = None;
+
+    let expanded = re.replace_all(s, |cap: &Captures| {
+        let var = cap.get(1).unwrap().as_str();
+        match env::var(var) {
+            Ok(v) => v,
+            Err(_) => {
+                err = Some(Error::Generic(format!("Environment variable `{}` in `_include` is not set", var)));
+                String::new()
+            }
+        }
+    }).into_owned();
+
+    match err {
+        Some(e) => Err(e),
+        None => Ok(expanded),
+    }
+}
+
+/// Strategies for merging an array with its counterpart in a less
+/// important data file. These are set per key using a `_merge`
+/// directive, e.g. `"_merge": { "vhosts": "merge_by:name" }`.
+#[derive(Debug, PartialEq)]
+enum MergeStrategy {
+    /// Concatenate the less important array onto the end (default)
+    Append,
+    /// Concatenate the less important array onto the start
+    Prepend,
+    /// Ignore the less important value, like the `!` key suffix
+    Replace,
+    /// Append, then remove duplicate values
+    Union,
+    /// Merge objects that share the same value for the given key
+    MergeBy(String),
+}
+
+impl MergeStrategy {
+    fn parse(strategy: &str) -> Result<MergeStrategy> {
+        match strategy {
+            "append" => Ok(MergeStrategy::Append),
+            "prepend" => Ok(MergeStrategy::Prepend),
+            "replace" => Ok(MergeStrategy::Replace),
+            "union" => Ok(MergeStrategy::Union),
+            s if s.starts_with("merge_by:") => {
+                let (_, key) = s.split_at(9);
+                let key = key.trim();
+
+                if key.is_empty() {
+                    Err(Error::Generic("Empty key in `merge_by` strategy".into()))
+                } else {
+                    Ok(MergeStrategy::MergeBy(key.into()))
+                }
+            },
+            s => Err(Error::Generic(format!("Unknown merge strategy `{}`", s))),
+        }
+    }
+}
+
+/// Remove `_merge` directives once merging is done, so they don't leak
+/// into the data that payloads see.
+fn strip_directives(value: &mut Value) {
+    match *value {
+        Value::Array(ref mut a) => {
+            for v in a {
+                strip_directives(v);
+            }
+        },
+        Value::Object(ref mut o) => {
+            o.remove("_merge");
+
+            for (_, v) in o.iter_mut() {
+                strip_directives(v);
+            }
+        },
+        _ => (),
+    }
+}
+
+fn merge_strategy(directives: &[Option<Value>], key: &str) -> Result<MergeStrategy> {
+    for d in directives {
+        if let Some(ref d) = *d {
+            if !d.is_object() {
+                return Err(Error::Generic("Value of `_merge` is not an object".into()));
+            }
+
+            if let Some(s) = d.get(key) {
+                return match s.as_str() {
+                    Some(s) => MergeStrategy::parse(s),
+                    None => Err(Error::Generic(format!("Merge strategy for `{}` is not a string", key))),
+                };
+            }
+        }
+    }
+
+    Ok(MergeStrategy::Append)
+}
+
+fn merge_values(into: Value, from: Value, strategy: &MergeStrategy, parent_from: &Value) -> Result<Value> {
     match into {
         Value::Null |
         Value::Bool(_) |
         Value::Number(_) |
         Value::String(_) => Ok(into),
         Value::Array(mut a) => {
-            if from.is_array() {
-                a.append(from.as_array_mut().unwrap());
-            }
-            else if !from.is_null() {
-                a.push(from);
-            }
+            let mut from = match from {
+                Value::Array(f) => f,
+                Value::Null => Vec::new(),
+                f => vec![f],
+            };
 
             let mut b = Vec::new();
 
+            match *strategy {
+                MergeStrategy::Append => a.append(&mut from),
+                MergeStrategy::Prepend => {
+                    from.append(&mut a);
+                    a = from;
+                },
+                MergeStrategy::Replace => (),
+                MergeStrategy::Union => {
+                    a.append(&mut from);
+
+                    let mut u = Vec::new();
+                    for v in a {
+                        if !u.contains(&v) {
+                            u.push(v);
+                        }
+                    }
+                    a = u;
+                },
+                MergeStrategy::MergeBy(ref key) => {
+                    for v in a {
+                        let pos = v.get(key).and_then(|k| from.iter().position(|f| f.get(key) == Some(k)));
+                        let f = match pos {
+                            Some(i) => from.remove(i),
+                            None => Value::Null,
+                        };
+                        b.push(try!(merge_values(v, f, &MergeStrategy::Append, parent_from)));
+                    }
+
+                    // Unmatched values are appended
+                    a = from;
+                },
+            }
+
             for v in a {
-                b.push(try!(merge_values(v, Value::Null, parent_from)));
+                b.push(try!(merge_values(v, Value::Null, &MergeStrategy::Append, parent_from)));
             }
 
             Ok(Value::Array(b))
         },
         Value::Object(o) => {
             let mut obj = Map::new();
+            let directives = [o.get("_merge").cloned(), from.get("_merge").cloned()];
 
             for (mut key, mut value) in o {
                 if key.ends_with("?") || key.ends_with("?!") {
@@ -123,15 +416,22 @@ fn merge_values(into: Value, mut from: Value, parent_from: &Value) -> Result<Val
                 }
 
                 let mut merge_val = Value::Null;
+                let strategy;
 
                 if key.ends_with("!") {
                     key.pop();
-                }
-                else if let Some(o1) = from.get(&key) {
-                    merge_val = o1.clone();
+                    strategy = MergeStrategy::Replace;
+                } else {
+                    strategy = try!(merge_strategy(&directives, &key));
+
+                    if strategy != MergeStrategy::Replace {
+                        if let Some(o1) = from.get(&key) {
+                            merge_val = o1.clone();
+                        }
+                    }
                 }
 
-                value = try!(merge_values(value, merge_val, &parent_from));
+                value = try!(merge_values(value, merge_val, &strategy, &parent_from));
 
                 obj.insert(key, value);
             }
@@ -181,10 +481,111 @@ fn query_value(data: &Value, value: Value) -> Result<Option<Value>> {
     Ok(None)
 }
 
+/// Resolve `{{/json/pointer}}` references in string values against
+/// the merged document.
+///
+/// A string that consists solely of one reference takes on the type
+/// of the referenced value. Otherwise each reference must resolve to
+/// a scalar, which is interpolated into the string.
+///
+/// Only references that start with `/` are resolved, so template
+/// fragments such as `{{name}}` pass through untouched. To keep a
+/// literal `{{/...}}`, e.g. a Mustache section end, prefix it with a
+/// backslash, which is written as `"\\{{/section}}"` in JSON.
+fn interpolate(data: Value) -> Result<Value> {
+    let re = Regex::new(r"(\\)?\{\{\s*(/[^{}]*?)\s*\}\}").unwrap();
+    let mut stack = Vec::new();
+    interpolate_value(&data, data.clone(), &re, &mut stack)
+}
+
+fn interpolate_value(root: &Value, value: Value, re: &Regex, stack: &mut Vec<String>) -> Result<Value> {
+    match value {
+        Value::String(s) => interpolate_str(root, s, re, stack),
+        Value::Array(a) => {
+            let mut b = Vec::new();
+
+            for v in a {
+                b.push(try!(interpolate_value(root, v, re, stack)));
+            }
+
+            Ok(Value::Array(b))
+        },
+        Value::Object(o) => {
+            let mut obj = Map::new();
+
+            for (key, value) in o {
+                obj.insert(key, try!(interpolate_value(root, value, re, stack)));
+            }
+
+            Ok(Value::Object(obj))
+        },
+        _ => Ok(value),
+    }
+}
+
+fn interpolate_str(root: &Value, s: String, re: &Regex, stack: &mut Vec<String>) -> Result<Value> {
+    if !re.is_match(&s) {
+        return Ok(Value::String(s));
+    }
+
+    if let Some(cap) = re.captures(&s) {
+        let m = cap.get(0).unwrap();
+        if m.start() == 0 && m.end() == s.len() && cap.get(1).is_none() {
+            return resolve_ref(root, cap.get(2).unwrap().as_str(), re, stack);
+        }
+    }
+
+    let mut buf = String::new();
+    let mut last = 0;
+
+    for cap in re.captures_iter(&s) {
+        let m = cap.get(0).unwrap();
+        buf.push_str(&s[last..m.start()]);
+        last = m.end();
+
+        // Escaped references are kept, minus the backslash
+        if cap.get(1).is_some() {
+            buf.push_str(&m.as_str()[1..]);
+            continue;
+        }
+
+        match try!(resolve_ref(root, cap.get(2).unwrap().as_str(), re, stack)) {
+            Value::String(v) => buf.push_str(&v),
+            Value::Number(n) => buf.push_str(&n.to_string()),
+            Value::Bool(b) => buf.push_str(&b.to_string()),
+            _ => return Err(Error::Generic(format!("Cannot interpolate non-scalar reference `{}` into string", m.as_str()))),
+        }
+    }
+
+    buf.push_str(&s[last..]);
+    Ok(Value::String(buf))
+}
+
+fn resolve_ref(root: &Value, pointer: &str, re: &Regex, stack: &mut Vec<String>) -> Result<Value> {
+    let pointer = pointer.to_string();
+
+    if stack.contains(&pointer) {
+        stack.push(pointer);
+        return Err(Error::Generic(format!("Circular reference in data: {}", stack.join(" -> "))));
+    }
+
+    let value = match root.pointer(&pointer) {
+        Some(v) => v.clone(),
+        None => return Err(Error::Generic(format!("Could not resolve reference `{}` in data", pointer))),
+    };
+
+    stack.push(pointer);
+    let value = try!(interpolate_value(root, value, re, stack));
+    stack.pop();
+
+    Ok(value)
+}
+
 #[cfg(test)]
 mod tests {
+    use error::Error;
     use serde_json::Value;
-    use std::fs;
+    use std::{env, fs};
     use std::io::Write;
     use std::path::PathBuf;
     use super::*;
@@ -214,6 +615,236 @@ mod tests {
         assert_eq!(value, expected_value);
     }
 
+    #[test]
+    fn test_data_as() {
+        let data = json!({
+            "nginx": {
+                "port": 80,
+                "vhosts": [ "example.com" ]
+            },
+            "bad": {
+                "port": "eighty"
+            }
+        });
+
+        let nginx: Nginx = data.data_as("/nginx").unwrap();
+        assert_eq!(nginx.port, 80);
+        assert_eq!(nginx.vhosts, vec!["example.com".to_string()]);
+        assert_eq!(data.data_as::<u16>("/nginx/port").unwrap(), 80);
+
+        let err = data.data_as::<Nginx>("/bad").err().unwrap();
+        assert!(err.to_string().contains("/bad"));
+        let err = data.data_as::<Nginx>("/missing").err().unwrap();
+        assert!(err.to_string().contains("/missing"));
+    }
+
+    #[derive(Deserialize)]
+    struct Nginx {
+        port: u16,
+        vhosts: Vec<String>,
+    }
+
+    #[test]
+    fn test_expand_include() {
+        let tempdir = TempDir::new("parser_test_include").unwrap();
+        let base = tempdir.path().display().to_string();
+
+        for dir in &["roles", "conf.d", "env"] {
+            fs::create_dir(format!("{}/{}", base, dir)).unwrap();
+        }
+
+        for file in &["roles/web.json", "roles/db.json", "roles/README", "conf.d/b.json", "conf.d/a.json", "env/test.json"] {
+            fs::File::create(format!("{}/{}", base, file)).unwrap();
+        }
+
+        assert_eq!(expand_include(&format!("{}/roles/*.json", base)).unwrap(), vec![
+            PathBuf::from(format!("{}/roles/db.json", base)),
+            PathBuf::from(format!("{}/roles/web.json", base)),
+        ]);
+        assert!(expand_include(&format!("{}/roles/*.yml", base)).is_err());
+        assert!(expand_include(&format!("optional:{}/roles/*.yml", base)).unwrap().is_empty());
+
+        assert_eq!(expand_include(&format!("{}/conf.d", base)).unwrap(), vec![
+            PathBuf::from(format!("{}/conf.d/a.json", base)),
+            PathBuf::from(format!("{}/conf.d/b.json", base)),
+        ]);
+
+        assert_eq!(expand_include(&format!("{}/moo.json", base)).unwrap(), vec![PathBuf::from(format!("{}/moo.json", base))]);
+        assert!(expand_include(&format!("optional: {}/moo.json", base)).unwrap().is_empty());
+
+        env::set_var("INAPI_TEST_INCLUDE_ENV", "test");
+        assert_eq!(expand_include(&format!("{}/env/${{INAPI_TEST_INCLUDE_ENV}}.json", base)).unwrap(), vec![
+            PathBuf::from(format!("{}/env/test.json", base)),
+        ]);
+        assert!(expand_include(&format!("{}/env/${{INAPI_TEST_INCLUDE_UNSET}}.json", base)).is_err());
+        assert!(expand_include(&format!("optional:{}/env/${{INAPI_TEST_INCLUDE_UNSET}}.json", base)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expand_include_relative() {
+        let tempdir = TempDir::new("parser_test_include_relative").unwrap();
+        let base = tempdir.path();
+        let data = base.join("data");
+
+        for dir in &["roles", "conf.d"] {
+            fs::create_dir_all(data.join(dir)).unwrap();
+        }
+
+        for file in &["roles/web.json", "roles/db.json", "roles/README", "conf.d/b.json", "conf.d/a.json", "conf.d/c.txt"] {
+            fs::File::create(data.join(file)).unwrap();
+        }
+
+        assert_eq!(expand_include_in(base, "roles/*.json").unwrap(), vec![
+            data.join("roles/db.json"),
+            data.join("roles/web.json"),
+        ]);
+        assert!(expand_include_in(base, "roles/*.yml").is_err());
+        assert!(expand_include_in(base, "optional:roles/*.yml").unwrap().is_empty());
+
+        assert_eq!(expand_include_in(base, "conf.d").unwrap(), vec![
+            data.join("conf.d/a.json"),
+            data.join("conf.d/b.json"),
+        ]);
+
+        assert_eq!(expand_include_in(base, "roles/web.json").unwrap(), vec![data.join("roles/web.json")]);
+        assert_eq!(expand_include_in(base, "optional:roles/web.json").unwrap(), vec![data.join("roles/web.json")]);
+        assert!(expand_include_in(base, "optional:roles/moo.json").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let tempdir = TempDir::new("parser_test_strategies").unwrap();
+        let mut path = tempdir.path().to_owned();
+        path.push("data");
+        fs::create_dir(&path).unwrap();
+
+        let bottom = format!("{}/bottom.json", path.display());
+        let mut fh = fs::File::create(&bottom).unwrap();
+        fh.write_all(json!({
+            "_merge": {
+                "vhosts": "merge_by:name"
+            },
+            "append": [ 3 ],
+            "prepend": [ 3 ],
+            "replace": [ 3 ],
+            "union": [ 2, 3 ],
+            "vhosts": [
+                { "name": "a.com", "port": 80, "aliases": [ "www.a.com" ] },
+                { "name": "b.com", "port": 80 }
+            ]
+        }).to_string().as_bytes()).unwrap();
+
+        path.push("top.json");
+        fh = fs::File::create(&path).unwrap();
+        fh.write_all(json!({
+            "_include": [ bottom ],
+            "_merge": {
+                "prepend": "prepend",
+                "replace": "replace",
+                "union": "union"
+            },
+            "append": [ 1, 2 ],
+            "prepend": [ 1, 2 ],
+            "replace": [ 1, 2 ],
+            "union": [ 1, 2 ],
+            "vhosts": [
+                { "name": "c.com", "port": 443 },
+                { "name": "a.com", "port": 443, "aliases": [ "a.net" ] }
+            ]
+        }).to_string().as_bytes()).unwrap();
+
+        let value = open(&path).unwrap();
+        let value = merge(value, Value::Null).unwrap();
+
+        assert_eq!(value["append"], json!([ 1, 2, 3 ]));
+        assert_eq!(value["prepend"], json!([ 3, 1, 2 ]));
+        assert_eq!(value["replace"], json!([ 1, 2 ]));
+        assert_eq!(value["union"], json!([ 1, 2, 3 ]));
+        assert_eq!(value["vhosts"], json!([
+            { "name": "c.com", "port": 443 },
+            { "name": "a.com", "port": 443, "aliases": [ "a.net", "www.a.com" ] },
+            { "name": "b.com", "port": 80 }
+        ]));
+        assert!(value.get("_merge").is_none());
+
+        assert!(MergeStrategy::parse("merge_by:").is_err());
+        assert!(MergeStrategy::parse("moo").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let tempdir = TempDir::new("parser_test_validate").unwrap();
+        let mut path = tempdir.path().to_owned();
+        path.push("payloads/nginx/data");
+        fs::create_dir_all(&path).unwrap();
+        path.push("schema.json");
+
+        let mut fh = fs::File::create(&path).unwrap();
+        fh.write_all(json!({
+            "properties": {
+                "port": { "type": "integer" }
+            }
+        }).to_string().as_bytes()).unwrap();
+
+        let base = tempdir.path();
+        assert!(validate_in(base, &json!({ "_payloads": [ "nginx::install" ], "port": 80 })).is_ok());
+        assert!(validate_in(base, &json!({ "_payloads": [ "nomatch" ], "port": "80" })).is_ok());
+
+        match validate_in(base, &json!({ "_payloads": [ "nginx::install" ], "port": "80" })) {
+            Err(Error::Schema(v)) => {
+                assert_eq!(v.len(), 1);
+                assert_eq!(v[0].path, "/port");
+            },
+            _ => panic!("Expected schema error"),
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let data = json!({
+            "app_name": "moo",
+            "port": 8080,
+            "logs": "/srv/{{/app_name}}/logs",
+            "listen": "{{ /_telemetry/net/0/ips/0 }}:{{/port}}",
+            "app_port": "{{/port}}",
+            "nested": {
+                "access_log": "{{/logs}}/access.log"
+            },
+            "_telemetry": {
+                "net": [{
+                    "ips": [ "127.0.0.1" ]
+                }]
+            }
+        });
+
+        let value = interpolate(data).unwrap();
+        assert_eq!(value["logs"], json!("/srv/moo/logs"));
+        assert_eq!(value["listen"], json!("127.0.0.1:8080"));
+        assert_eq!(value["app_port"], json!(8080));
+        assert_eq!(value["nested"]["access_log"], json!("/srv/moo/logs/access.log"));
+    }
+
+    #[test]
+    fn test_interpolate_literal() {
+        let data = json!({
+            "template": "Hello {{name}}{{#admin}} (admin)\\{{/admin}}",
+            "escaped": "\\{{/port}}",
+            "port": 8080
+        });
+
+        let value = interpolate(data).unwrap();
+        assert_eq!(value["template"], json!("Hello {{name}}{{#admin}} (admin){{/admin}}"));
+        assert_eq!(value["escaped"], json!("{{/port}}"));
+    }
+
+    #[test]
+    fn test_interpolate_fail() {
+        assert!(interpolate(json!({ "a": "{{/b}}" })).is_err());
+        assert!(interpolate(json!({ "a": "{{/b}}", "b": "{{/a}}" })).is_err());
+        assert!(interpolate(json!({ "a": { "b": "{{/a}}" } })).is_err());
+        assert!(interpolate(json!({ "a": "x{{/b}}", "b": [ 1 ] })).is_err());
+    }
+
     fn create_data(path: &mut PathBuf) -> Value {
         let mut fh = fs::File::create(format!("{}/data/middle.json", path.display())).unwrap();
         let payload_path = format!("{}/payloads/payload::default", path.display());
@@ -0,0 +1,228 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Validator for payload data schemas.
+//!
+//! Schemas are a subset of JSON Schema. The supported keywords are
+//! `type`, `enum`, `properties`, `required`, `additionalProperties`,
+//! `items`, `minimum`, `maximum` and `pattern`.
+
+use error::{Error, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+
+/// A single location in the data that does not match its schema.
+#[derive(Debug)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value
+    pub path: String,
+    /// Description of what the schema expected
+    pub expected: String,
+    /// The value found in the data
+    pub actual: Value,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(f, "{}: expected {}, got {}", path, self.expected, self.actual)
+    }
+}
+
+/// Validate data against a schema, returning every violation found.
+pub fn validate(schema: &Value, data: &Value) -> Result<Vec<SchemaViolation>> {
+    let mut violations = Vec::new();
+    try!(check(schema, data, "", &mut violations));
+    Ok(violations)
+}
+
+fn check(schema: &Value, data: &Value, path: &str, violations: &mut Vec<SchemaViolation>) -> Result<()> {
+    if !schema.is_object() {
+        return Err(Error::Generic(format!("Schema for `{}` is not an object", path)));
+    }
+
+    if let Some(t) = schema.get("type") {
+        let types = match *t {
+            Value::String(ref s) => vec![s.as_str()],
+            Value::Array(ref a) => {
+                let mut types = Vec::new();
+                for v in a {
+                    types.push(try!(v.as_str().ok_or(Error::Generic(format!("Schema type for `{}` must be a string", path)))));
+                }
+                types
+            },
+            _ => return Err(Error::Generic(format!("Schema type for `{}` must be a string or array", path))),
+        };
+
+        let mut matched = false;
+        for t in &types {
+            if try!(is_type(data, t)) {
+                matched = true;
+                break;
+            }
+        }
+
+        if !matched {
+            violations.push(violation(path, types.join(" or "), data));
+            // There's no point checking the structure of a value
+            // that is the wrong type.
+            return Ok(());
+        }
+    }
+
+    if let Some(e) = schema.get("enum") {
+        let opts = try!(e.as_array().ok_or(Error::Generic(format!("Schema enum for `{}` must be an array", path))));
+        if !opts.contains(data) {
+            let opts: Vec<String> = opts.iter().map(|o| o.to_string()).collect();
+            violations.push(violation(path, format!("one of [{}]", opts.join(", ")), data));
+        }
+    }
+
+    if let Some(n) = data.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64()) {
+            if n < min {
+                violations.push(violation(path, format!("minimum of {}", min), data));
+            }
+        }
+
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64()) {
+            if n > max {
+                violations.push(violation(path, format!("maximum of {}", max), data));
+            }
+        }
+    }
+
+    if let Some(s) = data.as_str() {
+        if let Some(p) = schema.get("pattern").and_then(|p| p.as_str()) {
+            if !try!(Regex::new(p)).is_match(s) {
+                violations.push(violation(path, format!("string matching `{}`", p), data));
+            }
+        }
+    }
+
+    if let Some(obj) = data.as_object() {
+        if let Some(req) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in req {
+                if let Some(key) = key.as_str() {
+                    if !obj.contains_key(key) {
+                        violations.push(violation(&child_path(path, key), "required value".into(), &Value::Null));
+                    }
+                }
+            }
+        }
+
+        let props = schema.get("properties").and_then(|p| p.as_object());
+
+        for (key, value) in obj {
+            match props.and_then(|p| p.get(key)) {
+                Some(s) => try!(check(s, value, &child_path(path, key), violations)),
+                None => {
+                    // Keys beginning with an underscore are reserved
+                    // for Intecture, e.g. `_telemetry`.
+                    if !key.starts_with("_") && schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        violations.push(violation(&child_path(path, key), "no additional properties".into(), value));
+                    }
+                },
+            }
+        }
+    }
+
+    if let Some(arr) = data.as_array() {
+        if let Some(items) = schema.get("items") {
+            for (i, value) in arr.iter().enumerate() {
+                try!(check(items, value, &child_path(path, &i.to_string()), violations));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_type(data: &Value, t: &str) -> Result<bool> {
+    Ok(match t {
+        "null" => data.is_null(),
+        "boolean" => data.is_boolean(),
+        "integer" => data.is_i64() || data.is_u64(),
+        "number" => data.is_number(),
+        "string" => data.is_string(),
+        "array" => data.is_array(),
+        "object" => data.is_object(),
+        _ => return Err(Error::Generic(format!("Unknown schema type `{}`", t))),
+    })
+}
+
+fn violation(path: &str, expected: String, actual: &Value) -> SchemaViolation {
+    SchemaViolation {
+        path: path.into(),
+        expected: expected,
+        actual: actual.clone(),
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace("~", "~0").replace("/", "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let schema = json!({
+            "type": "object",
+            "required": [ "hostname", "nginx" ],
+            "properties": {
+                "hostname": { "type": "string", "pattern": "^[a-z0-9.-]+$" },
+                "nginx": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                        "mode": { "enum": [ "proxy", "static" ] },
+                        "vhosts": { "type": "array", "items": { "type": "string" } }
+                    }
+                }
+            }
+        });
+
+        let data = json!({
+            "hostname": "web1.example.com",
+            "nginx": {
+                "port": 80,
+                "mode": "static",
+                "vhosts": [ "example.com" ]
+            },
+            "_telemetry": {}
+        });
+        assert!(validate(&schema, &data).unwrap().is_empty());
+
+        let data = json!({
+            "hostname": "WEB1",
+            "nginx": {
+                "port": 70000,
+                "mode": "cgi",
+                "vhosts": [ "example.com", 1 ],
+                "prot": 80
+            }
+        });
+        let violations = validate(&schema, &data).unwrap();
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(paths, vec!["/hostname", "/nginx/mode", "/nginx/port", "/nginx/prot", "/nginx/vhosts/1"]);
+        assert_eq!(violations[4].expected, "string");
+        assert_eq!(violations[4].actual, json!(1));
+
+        let violations = validate(&schema, &json!({ "hostname": 1 })).unwrap();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].path, "/nginx");
+        assert_eq!(violations[1].path, "/hostname");
+
+        assert!(validate(&json!({ "type": "moo" }), &json!(1)).is_err());
+    }
+}
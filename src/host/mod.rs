@@ -15,21 +15,28 @@ pub mod telemetry;
 
 pub use self::telemetry::TelemetryTarget;
 
+use command::Command;
 #[cfg(feature = "remote-run")]
 use czmq::{ZCert, ZMsg, ZSock, SocketType};
-#[cfg(feature = "remote-run")]
+use self::data::DataAs;
 use error::Error;
 use error::Result;
+use serde::Deserialize;
 #[cfg(feature = "remote-run")]
 use serde_json;
 use serde_json::Value;
 #[cfg(feature = "remote-run")]
 use std::mem;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 #[cfg(feature = "remote-run")]
 use zfilexfer;
 
+/// Shell command that creates the host's state directory and prints
+/// its path.
+pub const STATE_DIR_CMD: &'static str = "if [ \"$(id -u)\" -eq 0 ]; then d=/var/lib/inapi; m=755; else d=\"$HOME/.inapi\"; m=700; fi; \
+                                         mkdir -p -m $m \"$d\" && echo \"$d\"";
+
 #[cfg(feature = "local-run")]
 /// Primitive for communicating with a managed host.
 ///
@@ -84,7 +91,9 @@ impl Host {
         match path {
             Some(p) => {
                 let value = try!(data::open(p));
-                me.data = Rc::new(try!(data::merge(value, telemetry)));
+                let data = try!(data::merge(value, telemetry));
+                try!(data::validate(&data));
+                me.data = Rc::new(data);
             },
             None => me.data = Rc::new(telemetry),
         }
@@ -108,7 +117,9 @@ impl Host {
         mem::swap(&mut telemetry, &mut me.data);
         // We can use unwrap() here safely as we can guarantee that
         // there is only one strong reference to telemetry.
-        me.data = Rc::new(try!(data::merge(value, Rc::try_unwrap(telemetry).unwrap())));
+        let data = try!(data::merge(value, Rc::try_unwrap(telemetry).unwrap()));
+        try!(data::validate(&data));
+        me.data = Rc::new(data);
 
         Ok(me)
     }
@@ -179,6 +190,15 @@ impl Host {
         self.data.clone()
     }
 
+    /// Deserialize the Host's data at a JSON pointer into `T`.
+    ///
+    /// This is useful for pulling a whole section of config into a
+    /// struct, rather than retrieving each value with `needstr!` et
+    /// al.
+    pub fn data_as<T: Deserialize>(&self, pointer: &str) -> Result<T> {
+        self.data.data_as(pointer)
+    }
+
     #[cfg(feature = "remote-run")]
     fn lookup_server_cert(hostname: &str, user_cert: &ZCert) -> Result<ZCert> {
         let auth_cert = try!(ZCert::load("auth.crt"));
@@ -212,6 +232,19 @@ impl Host {
         }
     }
 
+    /// Get the directory where Intecture keeps state on the host,
+    /// creating it if needed. This is `/var/lib/inapi` for root, and
+    /// `~/.inapi` for other users, who can't write to `/var/lib`.
+    pub fn state_dir(&mut self) -> Result<PathBuf> {
+        let cmd = Command::new(STATE_DIR_CMD);
+        let result = try!(cmd.exec(self));
+        if result.exit_code != 0 || result.stdout.trim().is_empty() {
+            return Err(Error::Generic(format!("Could not create state directory: {}", result.stderr.trim())));
+        }
+
+        Ok(PathBuf::from(result.stdout.trim()))
+    }
+
     #[cfg(all(test, feature = "remote-run"))]
     pub fn test_new(hostname: Option<String>, api_sock: Option<ZSock>, file_sock: Option<ZSock>, data: Option<Value>) -> Host {
         let host = Host {
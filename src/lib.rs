@@ -21,6 +21,7 @@
 
 #[cfg(feature = "remote-run")]
 extern crate czmq;
+extern crate glob;
 #[cfg(not(feature = "local-run"))]
 #[macro_use]
 extern crate lazy_static;
@@ -33,6 +34,7 @@ extern crate serde;
 extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
+extern crate sha2;
 #[cfg(test)]
 extern crate tempdir;
 extern crate tempfile;
@@ -42,13 +44,16 @@ extern crate pnet;
 
 #[macro_use]
 mod ffi_helpers;
+mod archive;
 mod command;
+mod config;
 mod project;
 mod directory;
 mod error;
 mod file;
 #[macro_use]
 mod host;
+mod link;
 #[cfg(all(test, feature = "remote-run"))]
 mod mock_env;
 mod package;
@@ -57,15 +62,20 @@ mod payload;
 mod service;
 mod target;
 mod template;
+mod user;
 
+pub use archive::{Archive, ArchiveFormat, ExtractOpts};
 pub use command::{Command, CommandResult, ffi as command_ffi};
-pub use directory::{Directory, DirectoryOpts, ffi as directory_ffi};
+pub use config::{ConfigEdit, ConfigFile, ConfigFormat};
+pub use directory::{Directory, DirectoryOpts, SyncChange, SyncOpts, ffi as directory_ffi};
 pub use error::{Error, geterr};
-pub use file::{File, FileOwner, ffi as file_ffi};
+pub use file::{AclEntry, AclKind, File, FileChange, FileContent, FileEdit, FileOwner, FileSpec, FileStat, FileType, ffi as file_ffi};
 pub use host::{Host, ffi as host_ffi};
 pub use host::data::open as data_open;
+pub use host::data::{DataAs, SchemaViolation};
+pub use link::{Link, LinkType, ffi as link_ffi};
 pub use mustache::{MapBuilder, VecBuilder};
-pub use package::{Package, ffi as package_ffi};
+pub use package::{InstalledPackage, Package, PackageManager, PackageOutcome, PackageSet, PackageSetResult, PendingUpgrade, Repository, RepositorySpec, UpgradeResult, ffi as package_ffi};
 pub use package::providers::{Providers, ProviderFactory};
 #[cfg(feature = "remote-run")]
 pub use payload::{Payload, ffi as payload_ffi};
@@ -77,6 +87,7 @@ pub use project::{Language, ProjectConfig};
 pub use serde_json::Value;
 pub use service::{Service, ServiceRunnable, ffi as service_ffi};
 pub use template::{Template, ffi as template_ffi};
+pub use user::{Group, User};
 pub use zfilexfer::FileOptions;
 
 #[cfg(feature = "remote-run")]
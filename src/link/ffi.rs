@@ -0,0 +1,81 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! FFI interface for Link
+
+use ffi_helpers::Leaky;
+use host::Host;
+use libc::{c_char, int8_t, uint8_t};
+use std::ffi::CString;
+use super::*;
+
+#[no_mangle]
+pub extern "C" fn link_new(host_ptr: *const Host, path_ptr: *const c_char) -> *mut Link {
+    let mut host = Leaky::new(trynull!(readptr!(host_ptr, "Host pointer")));
+    let path = trynull!(ptrtostr!(path_ptr, "path string"));
+
+    let link = trynull!(Link::new(&mut host, path));
+    Box::into_raw(Box::new(link))
+}
+
+#[no_mangle]
+pub extern "C" fn link_read(link_ptr: *const Link, host_ptr: *const Host) -> *mut c_char {
+    let link = Leaky::new(trynull!(readptr!(link_ptr, "Link pointer")));
+    let mut host = Leaky::new(trynull!(readptr!(host_ptr, "Host pointer")));
+
+    let target = match trynull!(link.read(&mut host)) {
+        Some(t) => t.to_string_lossy().into_owned(),
+        None => String::new(),
+    };
+    trynull!(CString::new(target)).into_raw()
+}
+
+#[no_mangle]
+pub extern "C" fn link_ensure(link_ptr: *const Link, host_ptr: *const Host, target_ptr: *const c_char, hard: uint8_t) -> int8_t {
+    let link = Leaky::new(tryrc!(readptr!(link_ptr, "Link pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+    let target = tryrc!(ptrtostr!(target_ptr, "target string"), -1);
+    let link_type = if hard == 1 { LinkType::Hard } else { LinkType::Symbolic };
+
+    if tryrc!(link.ensure(&mut host, target, link_type), -1) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn link_delete(link_ptr: *const Link, host_ptr: *const Host) -> int8_t {
+    let link = Leaky::new(tryrc!(readptr!(link_ptr, "Link pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+
+    if tryrc!(link.delete(&mut host), -1) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn link_delete_hard(link_ptr: *const Link, host_ptr: *const Host, target_ptr: *const c_char) -> int8_t {
+    let link = Leaky::new(tryrc!(readptr!(link_ptr, "Link pointer"), -1));
+    let mut host = Leaky::new(tryrc!(readptr!(host_ptr, "Host pointer"), -1));
+    let target = tryrc!(ptrtostr!(target_ptr, "target string"), -1);
+
+    if tryrc!(link.delete_hard(&mut host, target), -1) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn link_free(link_ptr: *mut Link) -> uint8_t {
+    tryrc!(boxptr!(link_ptr, "Link pointer"));
+    0
+}
@@ -0,0 +1,262 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Link primitive.
+
+pub mod ffi;
+
+use error::{Error, Result};
+use host::Host;
+use std::path::{Path, PathBuf};
+use target::Target;
+
+/// Type of filesystem link.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinkType {
+    /// Symbolic link, which points to a path
+    Symbolic,
+    /// Hard link, which shares an inode with its target
+    Hard,
+}
+
+/// Primitive for managing symbolic and hard links.
+///
+/// Links are never followed, so a `Link` always refers to the link
+/// itself rather than the file it points to.
+///
+///# Example
+///
+/// ```no_run
+/// # use inapi::{Host, Link, LinkType};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let current = Link::new(&mut host, "/srv/app/current").unwrap();
+/// current.ensure(&mut host, "/srv/app/releases/42", LinkType::Symbolic).unwrap();
+/// assert_eq!(current.read(&mut host).unwrap().unwrap().to_str(), Some("/srv/app/releases/42"));
+/// ```
+pub struct Link {
+    /// Absolute path to link on managed host
+    path: PathBuf,
+}
+
+impl Link {
+    /// Create a new Link struct.
+    pub fn new<P: AsRef<Path>>(host: &mut Host, path: P) -> Result<Link> {
+        // Symlinks to directories are fine, but a real directory can't
+        // be replaced with a link.
+        if try!(Target::link_exists(host, path.as_ref()))
+            && try!(Target::directory_is_directory(host, path.as_ref()))
+            && try!(Target::link_read(host, path.as_ref())).is_none() {
+            return Err(Error::Generic("Path is a directory".to_string()));
+        }
+
+        Ok(Link {
+            path: path.as_ref().into(),
+        })
+    }
+
+    /// Check if anything exists at the link's path, without following
+    /// symlinks.
+    pub fn exists(&self, host: &mut Host) -> Result<bool> {
+        Target::link_exists(host, &self.path)
+    }
+
+    /// Get the path a symbolic link points to, or `None` if the path
+    /// is not a symbolic link.
+    pub fn read(&self, host: &mut Host) -> Result<Option<PathBuf>> {
+        Ok(try!(Target::link_read(host, &self.path)).map(|t| t.into()))
+    }
+
+    /// Ensure the link points to `target`, creating or retargeting it
+    /// as needed. Existing links are replaced atomically. Returns
+    /// `true` if the link was changed.
+    ///
+    /// Only symbolic links are replaced. If anything else exists at
+    /// the link's path, e.g. a regular file or a hard link to another
+    /// file, an error is returned rather than overwriting it.
+    pub fn ensure<P: AsRef<Path>>(&self, host: &mut Host, target: P, link_type: LinkType) -> Result<bool> {
+        let target = target.as_ref();
+
+        let existing = try!(self.read(host));
+        let current = match link_type {
+            LinkType::Symbolic => existing.as_ref().map_or(false, |t| t.as_path() == target),
+            LinkType::Hard => try!(Target::link_is_same_file(host, &self.path, &target.to_path_buf())),
+        };
+
+        if current {
+            return Ok(false);
+        }
+
+        if existing.is_none() && try!(self.exists(host)) {
+            return Err(Error::Generic(format!("{} exists and is not a symbolic link", self.path.display())));
+        }
+
+        try!(Target::link_create(host, &self.path, &target.to_path_buf(), link_type));
+        Ok(true)
+    }
+
+    /// Remove a symbolic link. Returns `true` if the link was removed,
+    /// or an error if the path is not a symbolic link.
+    pub fn delete(&self, host: &mut Host) -> Result<bool> {
+        if try!(self.read(host)).is_some() {
+            try!(Target::link_delete(host, &self.path));
+            Ok(true)
+        } else if try!(self.exists(host)) {
+            Err(Error::Generic(format!("{} is not a symbolic link", self.path.display())))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Remove a hard link to `target`. Returns `true` if the link was
+    /// removed, or an error if the path is not the same file as
+    /// `target`.
+    pub fn delete_hard<P: AsRef<Path>>(&self, host: &mut Host, target: P) -> Result<bool> {
+        if try!(Target::link_is_same_file(host, &self.path, &target.as_ref().to_path_buf())) {
+            try!(Target::link_delete(host, &self.path));
+            Ok(true)
+        } else if try!(self.exists(host)) {
+            Err(Error::Generic(format!("{} is not a hard link to {}", self.path.display(), target.as_ref().display())))
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+pub trait LinkTarget<P: AsRef<Path>> {
+    fn link_exists(host: &mut Host, path: P) -> Result<bool>;
+    fn link_read(host: &mut Host, path: P) -> Result<Option<String>>;
+    fn link_is_same_file(host: &mut Host, path: P, target: P) -> Result<bool>;
+    fn link_create(host: &mut Host, path: P, target: P, link_type: LinkType) -> Result<()>;
+    fn link_delete(host: &mut Host, path: P) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use czmq::{ZMsg, ZSys};
+    use host::Host;
+    #[cfg(feature = "local-run")]
+    use std::fs;
+    #[cfg(feature = "remote-run")]
+    use std::thread;
+    use super::*;
+    #[cfg(feature = "local-run")]
+    use tempdir::TempDir;
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_ensure() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let tempdir = TempDir::new("link_test_ensure").unwrap();
+        let release1 = tempdir.path().join("release1");
+        let release2 = tempdir.path().join("release2");
+        fs::create_dir(&release1).unwrap();
+        fs::create_dir(&release2).unwrap();
+
+        // Unrelated files named like the temporary link are left alone
+        fs::File::create(tempdir.path().join("current.inlink")).unwrap();
+
+        let current = Link::new(&mut host, tempdir.path().join("current")).unwrap();
+        assert!(current.read(&mut host).unwrap().is_none());
+        assert!(current.ensure(&mut host, &release1, LinkType::Symbolic).unwrap());
+        assert!(!current.ensure(&mut host, &release1, LinkType::Symbolic).unwrap());
+        assert!(current.ensure(&mut host, &release2, LinkType::Symbolic).unwrap());
+        assert_eq!(current.read(&mut host).unwrap(), Some(release2.clone()));
+        assert!(fs::symlink_metadata(tempdir.path().join("current.inlink")).unwrap().file_type().is_file());
+        assert_eq!(fs::read_dir(tempdir.path()).unwrap().count(), 4);
+
+        // Existing symlinks to directories can be managed too
+        assert!(Link::new(&mut host, tempdir.path().join("current")).is_ok());
+        assert!(Link::new(&mut host, &release1).is_err());
+
+        assert!(current.delete(&mut host).unwrap());
+        assert!(!current.delete(&mut host).unwrap());
+        assert!(release2.exists());
+
+        let file = tempdir.path().join("file");
+        fs::File::create(&file).unwrap();
+        let hard = Link::new(&mut host, tempdir.path().join("hard")).unwrap();
+        assert!(hard.ensure(&mut host, &file, LinkType::Hard).unwrap());
+        assert!(!hard.ensure(&mut host, &file, LinkType::Hard).unwrap());
+        assert!(hard.read(&mut host).unwrap().is_none());
+        assert!(hard.delete(&mut host).is_err());
+        assert!(hard.delete_hard(&mut host, &release1).is_err());
+        assert!(hard.delete_hard(&mut host, &file).unwrap());
+        assert!(file.exists());
+    }
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_regular_file() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let tempdir = TempDir::new("link_test_regular_file").unwrap();
+        let target = tempdir.path().join("target");
+        let file = tempdir.path().join("file");
+        fs::File::create(&target).unwrap();
+        fs::File::create(&file).unwrap();
+
+        let link = Link::new(&mut host, &file).unwrap();
+        assert!(link.ensure(&mut host, &target, LinkType::Symbolic).is_err());
+        assert!(link.ensure(&mut host, &target, LinkType::Hard).is_err());
+        assert!(link.delete(&mut host).is_err());
+        assert!(link.delete_hard(&mut host, &target).is_err());
+
+        let meta = fs::symlink_metadata(&file).unwrap();
+        assert!(meta.file_type().is_file());
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_ensure() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("link::exists", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/current", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("0").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("link::read", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/current", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("1").unwrap();
+            reply.addstr("/tmp/release1").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("link::create", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/current", msg.popstr().unwrap().unwrap());
+            assert_eq!("/tmp/release2", msg.popstr().unwrap().unwrap());
+            assert_eq!("symbolic", msg.popstr().unwrap().unwrap());
+
+            server.send_str("Ok").unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let link = Link::new(&mut host, "/tmp/current").unwrap();
+        assert!(link.ensure(&mut host, "/tmp/release2", LinkType::Symbolic).unwrap());
+
+        agent_mock.join().unwrap();
+    }
+}
@@ -27,6 +27,10 @@ pub enum Ffi__Providers {
     Pkg,
     Ports,
     Yum,
+    Pip,
+    Gem,
+    Npm,
+    Cargo,
 }
 
 impl convert::Into<Option<Providers>> for Ffi__Providers {
@@ -40,6 +44,10 @@ impl convert::Into<Option<Providers>> for Ffi__Providers {
             Ffi__Providers::Pkg => Some(Providers::Pkg),
             Ffi__Providers::Ports => Some(Providers::Ports),
             Ffi__Providers::Yum => Some(Providers::Yum),
+            Ffi__Providers::Pip => Some(Providers::Pip(None)),
+            Ffi__Providers::Gem => Some(Providers::Gem),
+            Ffi__Providers::Npm => Some(Providers::Npm(None)),
+            Ffi__Providers::Cargo => Some(Providers::Cargo),
         }
     }
 }
@@ -79,6 +87,37 @@ pub extern "C" fn package_install(pkg_ptr: *mut Package, host_ptr: *const Host)
     }
 }
 
+#[no_mangle]
+pub extern "C" fn package_install_version(pkg_ptr: *mut Package, host_ptr: *const Host, version_ptr: *const c_char) -> *mut Ffi__CommandResult {
+    let mut pkg = Leaky::new(trynull!(boxptr!(pkg_ptr, "Package pointer")));
+    let mut host = Leaky::new(trynull!(readptr!(host_ptr, "Host pointer")));
+    let version = trynull!(ptrtostr!(version_ptr, "version string"));
+
+    let result = trynull!(pkg.install_version(&mut host, version));
+    match result {
+        Some(r) => {
+            let ffi_r: Ffi__CommandResult = trynull!(catch_unwind(|| r.into()));
+            Box::into_raw(Box::new(ffi_r))
+        },
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn package_upgrade(pkg_ptr: *mut Package, host_ptr: *const Host) -> *mut Ffi__CommandResult {
+    let mut pkg = Leaky::new(trynull!(boxptr!(pkg_ptr, "Package pointer")));
+    let mut host = Leaky::new(trynull!(readptr!(host_ptr, "Host pointer")));
+
+    let result = trynull!(pkg.upgrade(&mut host));
+    match result {
+        Some(r) => {
+            let ffi_r: Ffi__CommandResult = trynull!(catch_unwind(|| r.into()));
+            Box::into_raw(Box::new(ffi_r))
+        },
+        None => ptr::null_mut(),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn package_uninstall(pkg_ptr: *mut Package, host_ptr: *const Host) -> *mut Ffi__CommandResult {
     let mut pkg = Leaky::new(trynull!(boxptr!(pkg_ptr, "Package pointer")));
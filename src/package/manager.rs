@@ -0,0 +1,200 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! System-wide package operations.
+
+use command::Command;
+use error::{Error, Result};
+use host::Host;
+use std::time::Duration;
+use super::providers::*;
+
+/// An upgrade that is available for an installed package.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingUpgrade {
+    /// Package name
+    pub name: String,
+    /// Installed version, if the provider reports it
+    pub current_version: Option<String>,
+    /// Version that will be installed
+    pub new_version: String,
+}
+
+/// Result of `PackageManager::upgrade`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeResult {
+    /// Packages that were upgraded
+    pub upgraded: Vec<PendingUpgrade>,
+    /// Packages that still have upgrades pending, e.g. because they
+    /// are held back
+    pub pending: Vec<PendingUpgrade>,
+}
+
+/// Primitive for operations that affect every package managed by a
+/// provider, such as refreshing its metadata and upgrading the system.
+///
+///# Example
+///
+/// ```no_run
+/// # use inapi::{Host, PackageManager};
+/// # use std::time::Duration;
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let manager = PackageManager::new(&mut host, None).unwrap();
+///
+/// // Only refresh the cache if it is more than an hour old
+/// manager.refresh(&mut host, Some(Duration::from_secs(3600))).unwrap();
+///
+/// let result = manager.upgrade(&mut host, true).unwrap();
+/// for upgrade in result.upgraded {
+///     println!("Upgraded {} to {}", upgrade.name, upgrade.new_version);
+/// }
+/// ```
+pub struct PackageManager {
+    /// The package source
+    provider: Box<Provider + 'static>,
+}
+
+impl PackageManager {
+    /// Create a new PackageManager.
+    pub fn new(host: &mut Host, providers: Option<Providers>) -> Result<PackageManager> {
+        Ok(PackageManager {
+            provider: try!(ProviderFactory::create(host, providers)),
+        })
+    }
+
+    /// Refresh the package metadata cache, unless it was refreshed
+    /// more recently than `max_age`. Returns `true` if the cache was
+    /// refreshed.
+    ///
+    /// Only refreshes made by Intecture are tracked, so the cache is
+    /// always refreshed the first time this is called on a host. Each
+    /// refresh is recorded in the host's state directory (see
+    /// `Host::state_dir`), and failing to record it is an error.
+    pub fn refresh(&self, host: &mut Host, max_age: Option<Duration>) -> Result<bool> {
+        if let Some(max_age) = max_age {
+            if let Some(age) = try!(cache_age(host, self.provider.get_providers())) {
+                if age < max_age.as_secs() {
+                    return Ok(false);
+                }
+            }
+        }
+
+        try!(refresh_cache(host, &*self.provider));
+        Ok(true)
+    }
+
+    /// List the packages that have upgrades available, optionally
+    /// limited to security upgrades.
+    pub fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        self.provider.pending_upgrades(host, security_only)
+    }
+
+    /// Upgrade every package that has an upgrade available, or only
+    /// those with security upgrades.
+    pub fn upgrade(&self, host: &mut Host, security_only: bool) -> Result<UpgradeResult> {
+        let before = try!(self.pending_upgrades(host, security_only));
+        if before.is_empty() {
+            return Ok(UpgradeResult { upgraded: Vec::new(), pending: Vec::new() });
+        }
+
+        let result = try!(self.provider.upgrade_all(host, security_only));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let pending = try!(self.pending_upgrades(host, security_only));
+        Ok(UpgradeResult {
+            upgraded: before.into_iter().filter(|u| !pending.iter().any(|p| p.name == u.name)).collect(),
+            pending: pending,
+        })
+    }
+}
+
+/// Path of the file that records when Intecture last refreshed a
+/// provider's cache. It's kept in the host's state directory, as a
+/// stamp that other users could write would let them stop the cache
+/// from being refreshed.
+fn stamp_path(host: &mut Host, providers: Providers) -> Result<String> {
+    // Drop any path that the provider carries, e.g. "Pip:/srv/venv"
+    let name = providers.to_string().split(':').next().unwrap_or("").to_lowercase();
+    let path = try!(host.state_dir()).join(format!("{}-refreshed", name));
+    Ok(quote(&path.to_string_lossy()))
+}
+
+/// Get the number of seconds since the cache was refreshed, according
+/// to the host's clock.
+fn cache_age(host: &mut Host, providers: Providers) -> Result<Option<u64>> {
+    let cmd = Command::new(&format!("cat {} 2>/dev/null; date +%s", try!(stamp_path(host, providers))));
+    let result = try!(cmd.exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    let times: Vec<u64> = result.stdout.lines().filter_map(|l| l.trim().parse().ok()).collect();
+    match times.len() {
+        2 => Ok(Some(times[1].saturating_sub(times[0]))),
+        _ => Ok(None),
+    }
+}
+
+/// Refresh a provider's cache and record when it happened.
+pub fn refresh_cache(host: &mut Host, provider: &Provider) -> Result<()> {
+    let result = try!(provider.refresh_cache(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    let cmd = Command::new(&format!("date +%s > {}", try!(stamp_path(host, provider.get_providers()))));
+    let result = try!(cmd.exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Generic(format!("Cache was refreshed but could not be recorded: {}", result.stderr.trim())));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    #[cfg(feature = "remote-run")]
+    use std::time::Duration;
+    #[cfg(feature = "remote-run")]
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_refresh() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("type apt-get", "/usr/bin/apt-get"),
+            mock::state_dir(),
+            mock::ok("cat '/var/lib/inapi/apt-refreshed' 2>/dev/null; date +%s", "1500000000\n1500000600\n"),
+            mock::state_dir(),
+            mock::ok("cat '/var/lib/inapi/apt-refreshed' 2>/dev/null; date +%s", "1500000000\n1500007200\n"),
+            mock::ok("apt-get update", ""),
+            mock::state_dir(),
+            mock::ok("date +%s > '/var/lib/inapi/apt-refreshed'", ""),
+            mock::ok("apt-get update", ""),
+            mock::state_dir(),
+            mock::err("date +%s > '/var/lib/inapi/apt-refreshed'", 1, ""),
+        ]);
+
+        let manager = PackageManager::new(&mut host, Some(Providers::Apt)).unwrap();
+
+        assert!(!manager.refresh(&mut host, Some(Duration::from_secs(3600))).unwrap());
+        assert!(manager.refresh(&mut host, Some(Duration::from_secs(3600))).unwrap());
+
+        // Failing to record the refresh is an error, rather than
+        // silently ignoring max_age from then on
+        assert!(manager.refresh(&mut host, None).is_err());
+
+        agent_mock.join().unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Mock agent for testing package providers against captured command
+//! output.
+
+use czmq::{ZMsg, ZSys};
+use host::{Host, STATE_DIR_CMD};
+use std::thread::{self, JoinHandle};
+
+/// A command the mock agent expects, and its reply.
+pub struct Reply {
+    /// Expected shell command
+    pub cmd: &'static str,
+    /// Exit code to reply with
+    pub exit_code: i32,
+    /// Standard output to reply with
+    pub stdout: &'static str,
+    /// Standard error to reply with
+    pub stderr: &'static str,
+}
+
+/// Shorthand for a successful `Reply`.
+pub fn ok(cmd: &'static str, stdout: &'static str) -> Reply {
+    Reply { cmd: cmd, exit_code: 0, stdout: stdout, stderr: "" }
+}
+
+/// Shorthand for a failed `Reply`.
+pub fn err(cmd: &'static str, exit_code: i32, stdout: &'static str) -> Reply {
+    Reply { cmd: cmd, exit_code: exit_code, stdout: stdout, stderr: "" }
+}
+
+/// Reply for `Host::state_dir`, as run by root.
+pub fn state_dir() -> Reply {
+    ok(STATE_DIR_CMD, "/var/lib/inapi\n")
+}
+
+/// Create a Host connected to a mock agent that expects each command
+/// in order. Join the returned handle to check that every command was
+/// run.
+pub fn host(replies: Vec<Reply>) -> (Host, JoinHandle<()>) {
+    ZSys::init();
+
+    let (client, mut server) = ZSys::create_pipe().unwrap();
+    client.set_rcvtimeo(Some(500));
+    server.set_rcvtimeo(Some(500));
+
+    let agent_mock = thread::spawn(move || {
+        for reply in replies {
+            let req = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("command::exec", req.popstr().unwrap().unwrap());
+            assert_eq!(reply.cmd, req.popstr().unwrap().unwrap());
+
+            let rep = ZMsg::new();
+            rep.addstr("Ok").unwrap();
+            rep.addstr(&reply.exit_code.to_string()).unwrap();
+            rep.addstr(reply.stdout).unwrap();
+            rep.addstr(reply.stderr).unwrap();
+            rep.send(&mut server).unwrap();
+        }
+    });
+
+    (Host::test_new(None, Some(client), None, None), agent_mock)
+}
@@ -9,12 +9,36 @@
 //! Package primitive.
 
 pub mod ffi;
+mod manager;
+#[cfg(all(test, feature = "remote-run"))]
+mod mock;
 pub mod providers;
+mod repository;
+mod set;
 
 use command::CommandResult;
 use error::Result;
 use host::Host;
 use self::providers::*;
+pub use self::manager::{PackageManager, PendingUpgrade, UpgradeResult};
+pub use self::repository::{Repository, RepositorySpec};
+pub use self::set::{PackageOutcome, PackageSet, PackageSetResult};
+
+/// A package that is installed on a host, as listed by
+/// `Package::inventory`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPackage {
+    /// Package name
+    pub name: String,
+    /// Installed version
+    pub version: String,
+    /// Architecture, e.g. `amd64` or `x86_64`, if the provider
+    /// records it
+    pub arch: Option<String>,
+    /// Repository the package was installed from, if the provider
+    /// records it
+    pub repository: Option<String>,
+}
 
 /// Primitive for installing and managing software packages.
 ///
@@ -74,6 +98,27 @@ impl Package {
         })
     }
 
+    /// List every package installed by the host's default provider.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # extern crate inapi;
+    /// # extern crate serde_json;
+    /// # use inapi::{Host, Package};
+    /// # fn main() {
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let inventory = Package::inventory(&mut host).unwrap();
+    /// println!("{}", serde_json::to_string(&inventory).unwrap());
+    /// # }
+    /// ```
+    pub fn inventory(host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let provider = try!(ProviderFactory::create(host, None));
+        provider.list_installed(host)
+    }
+
     /// Check if the package is installed.
     pub fn is_installed(&self) -> bool {
         self.installed
@@ -94,6 +139,67 @@ impl Package {
         }
     }
 
+    /// Get the installed version of the package, or `None` if it isn't
+    /// installed.
+    pub fn version(&self, host: &mut Host) -> Result<Option<String>> {
+        self.provider.installed_version(host, &self.name)
+    }
+
+    /// Get the version of the package that would be installed or
+    /// upgraded to, or `None` if no repository offers it.
+    pub fn candidate_version(&self, host: &mut Host) -> Result<Option<String>> {
+        self.provider.candidate_version(host, &self.name)
+    }
+
+    /// Install a specific version of the package, upgrading or
+    /// downgrading it as necessary.
+    ///
+    /// `version` is either an exact version, e.g. `1.18.0-1`, or a
+    /// glob matching a range of versions, e.g. `1.18.*`. Nothing is
+    /// done if the installed version already matches.
+    ///
+    ///# Example
+    ///
+    /// ```no_run
+    /// # use inapi::{Host, Package};
+    #[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+    #[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+    #[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+    /// let mut package = Package::new(&mut host, "nginx", None).unwrap();
+    /// package.install_version(&mut host, "1.18.*").unwrap();
+    /// ```
+    pub fn install_version(&mut self, host: &mut Host, version: &str) -> Result<Option<CommandResult>> {
+        if let Some(installed) = try!(self.version(host)) {
+            if version_matches(version, &installed) {
+                return Ok(None);
+            }
+        }
+
+        let result = try!(self.provider.install_version(host, &self.name, version));
+
+        if result.exit_code == 0 {
+            self.installed = true;
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Upgrade the package to the latest version, installing it if
+    /// necessary. Nothing is done if it is already up to date.
+    pub fn upgrade(&mut self, host: &mut Host) -> Result<Option<CommandResult>> {
+        if !self.installed {
+            return self.install(host);
+        }
+
+        let installed = try!(self.version(host));
+        let candidate = try!(self.candidate_version(host));
+        if candidate.is_none() || installed == candidate {
+            return Ok(None);
+        }
+
+        self.provider.upgrade(host, &self.name).map(Some)
+    }
+
     /// Uninstall the package.
     pub fn uninstall(&mut self, host: &mut Host) -> Result<Option<CommandResult>> {
         if self.installed {
@@ -121,6 +227,8 @@ mod tests {
     use czmq::{ZMsg, ZSys};
     use super::*;
     #[cfg(feature = "remote-run")]
+    use super::mock;
+    #[cfg(feature = "remote-run")]
     use super::providers::Providers;
     #[cfg(feature = "remote-run")]
     use std::thread;
@@ -148,12 +256,12 @@ mod tests {
 
             let req = ZMsg::recv(&mut server).unwrap();
             assert_eq!("command::exec", req.popstr().unwrap().unwrap());
-            assert_eq!("brew list", req.popstr().unwrap().unwrap());
+            assert_eq!("brew list --versions 'nginx'", req.popstr().unwrap().unwrap());
 
             let rep = ZMsg::new();
             rep.addstr("Ok").unwrap();
             rep.addstr("0").unwrap();
-            rep.addstr("nginx-filesystem").unwrap();
+            rep.addstr("").unwrap();
             rep.addstr("").unwrap();
             rep.send(&mut server).unwrap();
         });
@@ -167,6 +275,25 @@ mod tests {
         agent_mock.join().unwrap();
     }
 
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_install_version() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("type apt-get", "/usr/bin/apt-get"),
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'nginx'", "install ok installed\t1.14.0-1"),
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'nginx'", "install ok installed\t1.14.0-1"),
+            mock::ok("apt-get -y --allow-downgrades install 'nginx=1.18.*'", ""),
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'nginx'", "install ok installed\t1.18.0-6"),
+        ]);
+
+        let mut pkg = Package::new(&mut host, "nginx", Some(Providers::Apt)).unwrap();
+
+        assert!(pkg.install_version(&mut host, "1.18.*").unwrap().is_some());
+        assert!(pkg.install_version(&mut host, "1.18.*").unwrap().is_none());
+
+        agent_mock.join().unwrap();
+    }
+
     #[cfg(feature = "local-run")]
     #[test]
     fn test_new_default() {
@@ -206,12 +333,12 @@ mod tests {
 
             let req = ZMsg::recv(&mut server).unwrap();
             assert_eq!("command::exec", req.popstr().unwrap().unwrap());
-            assert_eq!("brew list", req.popstr().unwrap().unwrap());
+            assert_eq!("brew list --versions 'nginx'", req.popstr().unwrap().unwrap());
 
             let rep = ZMsg::new();
             rep.addstr("Ok").unwrap();
             rep.addstr("0").unwrap();
-            rep.addstr("abc def nginx pkg").unwrap();
+            rep.addstr("nginx 1.21.1").unwrap();
             rep.addstr("").unwrap();
             rep.send(&mut server).unwrap();
         });
@@ -9,8 +9,11 @@
 //! Apt package provider
 
 use command::{Command, CommandResult};
-use error::Result;
+use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use regex::Regex;
+use std::collections::HashMap;
 use super::*;
 
 pub struct Apt;
@@ -28,10 +31,57 @@ impl Provider for Apt {
     }
 
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        let cmd = Command::new(&format!("dpkg --get-selections | grep -E \"{}\\s+install$\"", name));
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("dpkg-query -W -f '${{Status}}\\t${{Version}}\\n' {}", quote(name)));
         let result = try!(cmd.exec(host));
 
-        Ok(result.exit_code == 0)
+        if result.exit_code != 0 {
+            return Ok(None);
+        }
+
+        // Multi-arch packages print a line per architecture, and
+        // removed packages keep their version until purged, so only
+        // trust the version of an installed line.
+        Ok(result.stdout.lines().filter_map(|l| {
+            let mut parts = l.splitn(2, '\t');
+            match (parts.next(), parts.next()) {
+                (Some(status), Some(version)) if status.ends_with(" installed") => Some(version.to_string()),
+                _ => None,
+            }
+        }).next())
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("apt-cache policy {}", quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(result.stdout.lines()
+                        .filter_map(|l| l.trim().split("Candidate: ").nth(1))
+                        .find(|v| *v != "(none)")
+                        .map(|v| v.to_string()))
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new(&format!("dpkg-query -W -f '${{Package}}\\t${{Status}}\\n' {}", quote_all(names)));
+        let result = try!(cmd.exec(host));
+
+        // Exits non-zero if any package is unknown, but still lists
+        // the others.
+        Ok(result.stdout.lines()
+                        .filter_map(|l| {
+                            let mut parts = l.splitn(2, '\t');
+                            match (parts.next(), parts.next()) {
+                                (Some(name), Some(status)) if status.ends_with(" installed") => Some(name.to_string()),
+                                _ => None,
+                            }
+                        })
+                        .collect())
     }
 
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
@@ -39,8 +89,260 @@ impl Provider for Apt {
         cmd.exec(host)
     }
 
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("apt-get -y --allow-downgrades install {}", quote(&format!("{}={}", name, version))));
+        cmd.exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("apt-get -y install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("apt-get -y --only-upgrade install {}", quote(name)));
+        cmd.exec(host)
+    }
+
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("apt-get -y remove {}", name));
         cmd.exec(host)
     }
+
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        let url = try!(spec.url.as_ref().ok_or(Error::Generic("Apt repositories require a URL".into())));
+        let distribution = try!(spec.distribution.as_ref().ok_or(Error::Generic("Apt repositories require a distribution".into())));
+        let mut changed = false;
+
+        let mut source = vec!["deb".to_string()];
+        if let Some(ref key_url) = spec.key_url {
+            let keyring = format!("/etc/apt/keyrings/{}.asc", name);
+            changed |= try!(fetch_key(host, "curl -fsSLo", key_url, &keyring));
+            source.push(format!("[signed-by={}]", keyring));
+        }
+        source.push(url.clone());
+        source.push(distribution.clone());
+        source.extend(spec.components.iter().cloned());
+
+        changed |= try!(ensure_file(host, &format!("/etc/apt/sources.list.d/{}.list", name), source.join(" ") + "\n"));
+        Ok(changed)
+    }
+
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        let list = try!(remove_file(host, &format!("/etc/apt/sources.list.d/{}.list", name)));
+        let key = try!(remove_file(host, &format!("/etc/apt/keyrings/{}.asc", name)));
+        Ok(list || key)
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("apt-get update");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        let cmd = Command::new("apt-get -s upgrade");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(try!(parse_simulation(&result.stdout)).into_iter()
+                                                 .filter(|&(_, ref origin)| !security_only || origin.to_lowercase().contains("security"))
+                                                 .map(|(upgrade, _)| upgrade)
+                                                 .collect())
+    }
+
+    /// Apt has no notion of security upgrades, so a security-only
+    /// upgrade installs the pending upgrades from security archives.
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let cmd = if security_only {
+            let names: Vec<String> = try!(self.pending_upgrades(host, true)).into_iter().map(|u| u.name).collect();
+            if names.is_empty() {
+                Command::new("true")
+            } else {
+                let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+                Command::new(&format!("apt-get -y --only-upgrade install {}", quote_all(&names)))
+            }
+        } else {
+            Command::new("apt-get -y upgrade")
+        };
+        cmd.exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("dpkg-query -W -f '${db:Status-Abbrev}\\t${Package}\\t${Version}\\t${Architecture}\\n'");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let mut packages: Vec<InstalledPackage> = result.stdout.lines().filter_map(|l| {
+            let cols: Vec<&str> = l.split('\t').collect();
+            if cols.len() == 4 && cols[0].starts_with("ii") {
+                Some(InstalledPackage {
+                    name: cols[1].to_string(),
+                    version: cols[2].to_string(),
+                    arch: Some(cols[3].to_string()),
+                    repository: None,
+                })
+            } else {
+                None
+            }
+        }).collect();
+
+        // dpkg doesn't record where packages came from, so ask apt
+        // which archive provides each installed version.
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        if !names.is_empty() {
+            let cmd = Command::new(&format!("apt-cache policy {}", quote_all(&names)));
+            let result = try!(cmd.exec(host));
+            if result.exit_code != 0 {
+                return Err(Error::Agent(result.stderr));
+            }
+
+            let origins = parse_policy_origins(&result.stdout);
+            for package in &mut packages {
+                package.repository = origins.get(&package.name).cloned();
+            }
+        }
+
+        Ok(packages)
+    }
+}
+
+/// Parse the upgrades from `apt-get -s` output, along with the
+/// origins they come from, e.g.
+/// `Inst bash [5.0-6ubuntu1] (5.0-6ubuntu1.1 Ubuntu:20.04/focal-security [amd64])`.
+fn parse_simulation(output: &str) -> Result<Vec<(PendingUpgrade, String)>> {
+    let re = try!(Regex::new(r"(?m)^Inst (\S+) (?:\[(\S+)\] )?\((\S+) ([^\[\)]*)"));
+
+    Ok(re.captures_iter(output).map(|cap| {
+        (PendingUpgrade {
+            name: cap[1].to_string(),
+            current_version: cap.get(2).map(|v| v.as_str().to_string()),
+            new_version: cap[3].to_string(),
+        }, cap[4].trim().to_string())
+    }).collect())
+}
+
+/// Parse the archive that each installed version comes from out of
+/// `apt-cache policy` output, e.g. `focal-updates/main` from:
+///
+/// ```text
+/// bash:
+///   Installed: 5.0-6ubuntu1.1
+///   Candidate: 5.0-6ubuntu1.1
+///   Version table:
+///  *** 5.0-6ubuntu1.1 500
+///         500 http://archive.ubuntu.com/ubuntu focal-updates/main amd64 Packages
+///         100 /var/lib/dpkg/status
+/// ```
+///
+/// Packages that are only known to dpkg have no archive.
+fn parse_policy_origins(output: &str) -> HashMap<String, String> {
+    let mut origins = HashMap::new();
+    let mut name = None;
+    let mut in_installed = false;
+
+    for line in output.lines() {
+        if !line.starts_with(' ') && line.ends_with(':') {
+            // Multiarch packages may be qualified, e.g. `libc6:i386:`
+            name = line.trim_right_matches(':').split(':').next().map(|n| n.to_string());
+            in_installed = false;
+        } else if line.starts_with(" *** ") {
+            in_installed = true;
+        } else if line.starts_with("     ") && !line.starts_with("        ") {
+            // Another version in the table
+            in_installed = false;
+        } else if in_installed {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() >= 3 && cols[1] != "/var/lib/dpkg/status" {
+                if let Some(ref n) = name {
+                    origins.entry(n.clone()).or_insert(cols[2].to_string());
+                }
+            }
+        }
+    }
+
+    origins
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_is_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'g++'", "install ok installed\t4:9.3.0-1ubuntu2\n"),
+            mock::err("dpkg-query -W -f '${Status}\\t${Version}\\n' 'libc6-dev'", 1, ""),
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'nginx'", "deinstall ok config-files\t1.18.0-0ubuntu1\n"),
+            mock::ok("dpkg-query -W -f '${Status}\\t${Version}\\n' 'libc6'", "deinstall ok config-files\t2.31-0ubuntu9\ninstall ok installed\t2.31-0ubuntu9.9\n"),
+        ]);
+
+        assert!(Apt.is_installed(&mut host, "g++").unwrap());
+        assert!(!Apt.is_installed(&mut host, "libc6-dev").unwrap());
+        assert!(!Apt.is_installed(&mut host, "nginx").unwrap());
+        assert_eq!(Apt.installed_version(&mut host, "libc6").unwrap(), Some("2.31-0ubuntu9.9".into()));
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_candidate_version() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("apt-cache policy 'nginx'", "nginx:\n  Installed: (none)\n  Candidate: 1.18.0-0ubuntu1\n  Version table:\n"),
+            mock::ok("apt-cache policy 'nosuchpkg'", ""),
+        ]);
+
+        assert_eq!(Apt.candidate_version(&mut host, "nginx").unwrap(), Some("1.18.0-0ubuntu1".into()));
+        assert_eq!(Apt.candidate_version(&mut host, "nosuchpkg").unwrap(), None);
+
+        agent_mock.join().unwrap();
+    }
+
+    #[test]
+    fn test_parse_policy_origins() {
+        let output = "bash:\n\
+                      \x20 Installed: 5.0-6ubuntu1.1\n\
+                      \x20 Candidate: 5.0-6ubuntu1.1\n\
+                      \x20 Version table:\n\
+                      \x20*** 5.0-6ubuntu1.1 500\n\
+                      \x20       500 http://archive.ubuntu.com/ubuntu focal-updates/main amd64 Packages\n\
+                      \x20       100 /var/lib/dpkg/status\n\
+                      \x20    5.0-6ubuntu1 500\n\
+                      \x20       500 http://archive.ubuntu.com/ubuntu focal/main amd64 Packages\n\
+                      local-tool:\n\
+                      \x20 Installed: 1.0\n\
+                      \x20 Candidate: 1.0\n\
+                      \x20 Version table:\n\
+                      \x20*** 1.0 100\n\
+                      \x20       100 /var/lib/dpkg/status\n";
+        let origins = parse_policy_origins(output);
+
+        assert_eq!(origins.get("bash"), Some(&"focal-updates/main".to_string()));
+        assert_eq!(origins.get("local-tool"), None);
+    }
+
+    #[test]
+    fn test_parse_simulation() {
+        let output = "Reading package lists...\n\
+                      Inst bash [5.0-6ubuntu1] (5.0-6ubuntu1.1 Ubuntu:20.04/focal-security [amd64])\n\
+                      Inst tzdata [2021a-0ubuntu0.20.04] (2021e-0ubuntu0.20.04 Ubuntu:20.04/focal-updates [all])\n\
+                      Inst libnew (1.0-1 Ubuntu:20.04/focal-updates [amd64])\n\
+                      Conf bash (5.0-6ubuntu1.1 Ubuntu:20.04/focal-security [amd64])\n";
+        let upgrades = parse_simulation(output).unwrap();
+
+        assert_eq!(upgrades.len(), 3);
+        assert_eq!(upgrades[0], (PendingUpgrade {
+            name: "bash".into(),
+            current_version: Some("5.0-6ubuntu1".into()),
+            new_version: "5.0-6ubuntu1.1".into(),
+        }, "Ubuntu:20.04/focal-security".into()));
+        assert_eq!(upgrades[2].0.current_version, None);
+    }
 }
@@ -0,0 +1,241 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Cargo package provider, for binaries installed with `cargo install`
+
+use command::{Command, CommandResult};
+use error::{Error, Result};
+use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use super::*;
+
+pub struct Cargo;
+
+/// A crate installed with `cargo install`.
+#[derive(Debug, PartialEq)]
+struct InstalledCrate {
+    name: String,
+    version: String,
+    /// Whether the crate came from crates.io, rather than a local path
+    /// or git repository
+    from_registry: bool,
+}
+
+impl Cargo {
+    /// Get each installed crate.
+    fn list(&self, host: &mut Host) -> Result<Vec<InstalledCrate>> {
+        let cmd = Command::new("cargo install --list");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_install_list(&result.stdout))
+    }
+}
+
+impl Provider for Cargo {
+    fn get_providers(&self) -> Providers {
+        Providers::Cargo
+    }
+
+    fn is_active(&self, host: &mut Host) -> Result<bool> {
+        let cmd = Command::new("type cargo");
+        let result = try!(cmd.exec(host));
+
+        Ok(result.exit_code == 0)
+    }
+
+    fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        Ok(try!(self.list(host)).into_iter().find(|c| c.name == name).map(|c| c.version))
+    }
+
+    /// Searches crates.io, where the exact name may not be the first
+    /// result.
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("cargo search --limit 100 {}", quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_search(&result.stdout, name))
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let installed = try!(self.list(host));
+
+        Ok(names.iter()
+                .filter(|n| installed.iter().any(|c| c.name.as_str() == **n))
+                .map(|n| n.to_string())
+                .collect())
+    }
+
+    fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("cargo install {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    /// Cargo understands globs such as `1.18.*` as version
+    /// requirements, and replaces any other installed version.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        if version.contains(|c: char| c == '?' || c == '[') {
+            return Err(Error::Generic("Cargo only supports version ranges using `*`".into()));
+        }
+
+        let cmd = Command::new(&format!("cargo install {} --version {}", quote(name), quote(version)));
+        cmd.exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("cargo install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    /// Installing a crate again replaces it if a newer version exists.
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.install(host, name)
+    }
+
+    fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("cargo uninstall {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Cargo does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Cargo does not support repositories".into()))
+    }
+
+    /// Cargo updates the registry index whenever it installs, so there
+    /// is nothing to refresh.
+    #[allow(unused_variables)]
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        Ok(no_op())
+    }
+
+    /// Cargo can't list outdated crates, so this searches the registry
+    /// for each installed crate. Crates installed from a path or git
+    /// repository are skipped, as the registry's crate of the same
+    /// name may be unrelated.
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Cargo does not support security-only upgrades".into()));
+        }
+
+        let mut pending = Vec::new();
+        for installed in try!(self.list(host)).into_iter().filter(|c| c.from_registry) {
+            if let Some(candidate) = try!(self.candidate_version(host, &installed.name)) {
+                if candidate != installed.version {
+                    pending.push(PendingUpgrade {
+                        name: installed.name,
+                        current_version: Some(installed.version),
+                        new_version: candidate,
+                    });
+                }
+            }
+        }
+
+        Ok(pending)
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let pending = try!(self.pending_upgrades(host, security_only));
+        if pending.is_empty() {
+            return Ok(no_op());
+        }
+
+        let names: Vec<&str> = pending.iter().map(|p| p.name.as_str()).collect();
+        self.install_many(host, &names)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        Ok(try!(self.list(host)).into_iter().map(|c| InstalledPackage {
+            name: c.name,
+            version: c.version,
+            arch: None,
+            repository: None,
+        }).collect())
+    }
+}
+
+/// Parse `cargo install --list` output, which lists each crate followed
+/// by its binaries, e.g.:
+///
+/// ```text
+/// ripgrep v13.0.0:
+///     rg
+/// mytool v0.1.0 (/home/me/mytool):
+///     mytool
+/// ```
+///
+/// Crates from a path or git repository have their source in brackets.
+fn parse_install_list(output: &str) -> Vec<InstalledCrate> {
+    output.lines()
+          .filter(|l| !l.starts_with(char::is_whitespace) && l.ends_with(':'))
+          .filter_map(|l| {
+              let mut parts = l.trim_right_matches(':').split_whitespace();
+              match (parts.next(), parts.next()) {
+                  (Some(name), Some(version)) => Some(InstalledCrate {
+                      name: name.to_string(),
+                      version: version.trim_left_matches('v').to_string(),
+                      from_registry: parts.next().is_none(),
+                  }),
+                  _ => None,
+              }
+          })
+          .collect()
+}
+
+/// Parse the version of a crate from `cargo search` output, e.g.
+/// `ripgrep = "14.1.0"    # ripgrep is a line-oriented search tool`.
+fn parse_search(output: &str, name: &str) -> Option<String> {
+    output.lines().filter_map(|l| {
+        let mut parts = l.splitn(2, " = ");
+        match (parts.next(), parts.next()) {
+            (Some(n), Some(rest)) if n == name => rest.split('"').nth(1).map(|v| v.to_string()),
+            _ => None,
+        }
+    }).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_install_list() {
+        let output = "ripgrep v13.0.0:\n    rg\nmytool v0.1.0 (/home/me/mytool):\n    mytool\n\
+                      foo v0.2.0 (https://github.com/me/foo#0a1b2c3d):\n    foo\n";
+
+        assert_eq!(parse_install_list(output), vec![
+            InstalledCrate { name: "ripgrep".into(), version: "13.0.0".into(), from_registry: true },
+            InstalledCrate { name: "mytool".into(), version: "0.1.0".into(), from_registry: false },
+            InstalledCrate { name: "foo".into(), version: "0.2.0".into(), from_registry: false },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let output = "ripgrep_all = \"0.10.6\"    # rga: ripgrep, but also search in PDFs\n\
+                      ripgrep = \"14.1.0\"    # ripgrep is a line-oriented search tool\n\
+                      ... and 95 crates more (use --limit N to see more)\n";
+
+        assert_eq!(parse_search(output, "ripgrep"), Some("14.1.0".into()));
+        assert_eq!(parse_search(output, "rg"), None);
+    }
+}
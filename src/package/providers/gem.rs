@@ -0,0 +1,252 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Gem package provider
+
+use command::{Command, CommandResult};
+use error::{Error, Result};
+use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use super::*;
+
+pub struct Gem;
+
+impl Provider for Gem {
+    fn get_providers(&self) -> Providers {
+        Providers::Gem
+    }
+
+    fn is_active(&self, host: &mut Host) -> Result<bool> {
+        let cmd = Command::new("type gem");
+        let result = try!(cmd.exec(host));
+
+        Ok(result.exit_code == 0)
+    }
+
+    fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("gem list --local --exact {}", quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(find_latest(&result.stdout, name))
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("gem list --remote --exact {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        Ok(if result.exit_code == 0 { find_latest(&result.stdout, name) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new("gem list --local");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let installed = parse_gem_list(&result.stdout);
+        Ok(names.iter()
+                .filter(|n| installed.iter().any(|&(ref name, _)| name.as_str() == **n))
+                .map(|n| n.to_string())
+                .collect())
+    }
+
+    fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("gem install --no-document {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    /// Globs ending in `.*`, e.g. `1.18.*`, are converted to a
+    /// pessimistic requirement, e.g. `~> 1.18.0`.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        let requirement = if version.ends_with(".*") {
+            format!("~> {}.0", &version[..version.len() - 2])
+        } else {
+            version.to_string()
+        };
+
+        if requirement.contains(|c: char| c == '*' || c == '?' || c == '[') {
+            return Err(Error::Generic("Gem only supports version ranges ending in `.*`".into()));
+        }
+
+        let cmd = Command::new(&format!("gem install --no-document {} --version {}", quote(name), quote(&requirement)));
+        cmd.exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("gem install --no-document {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("gem update --no-document {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("gem uninstall --all --executables {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Gem does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Gem does not support repositories".into()))
+    }
+
+    /// Gem queries its sources directly, so has no cache.
+    #[allow(unused_variables)]
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        Ok(no_op())
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Gem does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("gem outdated");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_outdated(&result.stdout))
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        if security_only {
+            return Err(Error::Generic("Gem does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("gem update --no-document");
+        cmd.exec(host)
+    }
+
+    /// Each installed version of a gem is listed separately.
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("gem list --local");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let mut packages = Vec::new();
+        for (name, versions) in parse_gem_list(&result.stdout) {
+            for version in versions {
+                packages.push(InstalledPackage {
+                    name: name.clone(),
+                    version: version,
+                    arch: None,
+                    repository: None,
+                });
+            }
+        }
+
+        Ok(packages)
+    }
+}
+
+/// Parse `gem list` output into each gem's versions, newest first, e.g.
+/// `rake (13.0.6, 12.3.3)` or `json (default: 2.6.1)`. Platforms such
+/// as `nokogiri (1.15.4 x86_64-linux)` are dropped.
+fn parse_gem_list(output: &str) -> Vec<(String, Vec<String>)> {
+    output.lines().filter_map(|l| {
+        let mut parts = l.trim().splitn(2, " (");
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(versions)) if versions.ends_with(')') => {
+                let versions = versions[..versions.len() - 1].split(", ")
+                                                             .filter_map(|v| v.trim_left_matches("default: ").split_whitespace().next())
+                                                             .map(|v| v.to_string())
+                                                             .collect();
+                Some((name.to_string(), versions))
+            },
+            _ => None,
+        }
+    }).collect()
+}
+
+/// Find the newest version of a gem in `gem list` output.
+fn find_latest(output: &str, name: &str) -> Option<String> {
+    parse_gem_list(output).into_iter()
+                          .find(|&(ref n, _)| n == name)
+                          .and_then(|(_, versions)| versions.into_iter().next())
+}
+
+/// Parse `gem outdated` output, e.g. `rake (12.3.3 < 13.0.6)`.
+fn parse_outdated(output: &str) -> Vec<PendingUpgrade> {
+    output.lines().filter_map(|l| {
+        let mut parts = l.trim().splitn(2, " (");
+        match (parts.next(), parts.next().map(|v| v.trim_right_matches(')').split(" < ").collect::<Vec<_>>())) {
+            (Some(name), Some(ref versions)) if versions.len() == 2 => Some(PendingUpgrade {
+                name: name.to_string(),
+                current_version: Some(versions[0].to_string()),
+                new_version: versions[1].to_string(),
+            }),
+            _ => None,
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[test]
+    fn test_parse_gem_list() {
+        let output = "\n*** LOCAL GEMS ***\n\n\
+                      json (default: 2.6.1)\n\
+                      nokogiri (1.15.4 x86_64-linux)\n\
+                      rake (13.0.6, 12.3.3)\n";
+
+        assert_eq!(parse_gem_list(output), vec![
+            ("json".to_string(), vec!["2.6.1".to_string()]),
+            ("nokogiri".to_string(), vec!["1.15.4".to_string()]),
+            ("rake".to_string(), vec!["13.0.6".to_string(), "12.3.3".to_string()]),
+        ]);
+        assert_eq!(find_latest(output, "rake"), Some("13.0.6".into()));
+        assert_eq!(find_latest(output, "rails"), None);
+    }
+
+    #[test]
+    fn test_parse_outdated() {
+        assert_eq!(parse_outdated("rake (12.3.3 < 13.0.6)\n"), vec![PendingUpgrade {
+            name: "rake".into(),
+            current_version: Some("12.3.3".into()),
+            new_version: "13.0.6".into(),
+        }]);
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_install_version() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("gem install --no-document 'rails' --version '~> 7.1.0'", ""),
+            mock::ok("gem install --no-document 'rails' --version '7.1.2'", ""),
+        ]);
+
+        assert_eq!(Gem.install_version(&mut host, "rails", "7.1.*").unwrap().exit_code, 0);
+        assert_eq!(Gem.install_version(&mut host, "rails", "7.1.2").unwrap().exit_code, 0);
+        assert!(Gem.install_version(&mut host, "rails", "7.*.2").is_err());
+
+        agent_mock.join().unwrap();
+    }
+}
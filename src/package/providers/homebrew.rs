@@ -11,7 +11,9 @@
 use command::{Command, CommandResult};
 use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
 use regex::Regex;
+use serde_json;
 use super::*;
 
 pub struct Homebrew;
@@ -29,14 +31,49 @@ impl Provider for Homebrew {
     }
 
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        let cmd = Command::new("brew list");
+        let cmd = Command::new(&format!("brew list --versions {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        // Exits non-zero or prints nothing if the formula isn't
+        // installed, depending on the Homebrew version.
+        Ok(result.exit_code == 0 && !result.stdout.trim().is_empty())
+    }
+
+    /// The formula's revision, e.g. the `_1` in `1.21.1_1`, is dropped
+    /// so that the version can be compared with `candidate_version`.
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("brew list --versions {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        // Output is the name followed by each installed version, with
+        // the active version last.
+        if result.exit_code == 0 {
+            Ok(result.stdout.split_whitespace().skip(1).last().map(|v| strip_revision(v).to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("brew info --json=v1 {}", quote(name)));
         let result = try!(cmd.exec(host));
         if result.exit_code != 0 {
-            return Err(Error::Agent(result.stderr));
+            return Ok(None);
         }
 
-        let re = try!(Regex::new(&format!("(?m)(^|\\s+){}\\s+", name)));
-        Ok(re.is_match(&result.stdout))
+        let info: serde_json::Value = try!(serde_json::from_str(&result.stdout));
+        Ok(info.pointer("/0/versions/stable").and_then(|v| v.as_str()).map(|v| v.to_string()))
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new(&format!("brew list --versions {}", quote_all(names)));
+        let result = try!(cmd.exec(host));
+
+        Ok(result.stdout.lines()
+                        .filter_map(|l| l.split_whitespace().next())
+                        .filter(|n| names.contains(n))
+                        .map(|n| n.to_string())
+                        .collect())
     }
 
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
@@ -44,8 +81,188 @@ impl Provider for Homebrew {
         cmd.exec(host)
     }
 
+    /// Homebrew only offers the current version of each formula, so
+    /// this fails unless the current version satisfies `version`.
+    /// Older versions are published as separate formulae, e.g.
+    /// `postgresql@9.6`.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        match try!(self.candidate_version(host, name)) {
+            Some(ref candidate) if version_matches(version, candidate) => self.install(host, name),
+            Some(candidate) => Err(Error::Generic(format!("Homebrew cannot install {} {}, only {}", name, version, candidate))),
+            None => Err(Error::Generic(format!("Homebrew has no formula named {}", name))),
+        }
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("brew install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("brew upgrade {}", quote(name)));
+        cmd.exec(host)
+    }
+
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("brew uninstall {}", name));
         cmd.exec(host)
     }
+
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        if try!(is_tapped(host, name)) {
+            return Ok(false);
+        }
+
+        let url = spec.url.as_ref().map(|u| format!(" {}", quote(u))).unwrap_or(String::new());
+        let cmd = Command::new(&format!("brew tap {}{}", quote(name), url));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(true)
+    }
+
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        if !try!(is_tapped(host, name)) {
+            return Ok(false);
+        }
+
+        let cmd = Command::new(&format!("brew untap {}", quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(true)
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("brew update");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Homebrew does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("brew outdated --verbose");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        // Lines look like `openssl@1.1 (1.1.1k, 1.1.1l) < 1.1.1m`, where
+        // the last installed version is the active one.
+        let re = try!(Regex::new(r"(?m)^(\S+) \((.+)\) < (\S+)"));
+        Ok(re.captures_iter(&result.stdout).map(|cap| PendingUpgrade {
+            name: cap[1].to_string(),
+            current_version: cap[2].split(", ").last().map(|v| v.to_string()),
+            new_version: cap[3].to_string(),
+        }).collect())
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        if security_only {
+            return Err(Error::Generic("Homebrew does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("brew upgrade");
+        cmd.exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("brew info --json=v1 --installed");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let formulae: Vec<serde_json::Value> = try!(serde_json::from_str(&result.stdout));
+        Ok(formulae.iter().filter_map(|f| {
+            let name = match f.get("name").and_then(|n| n.as_str()) {
+                Some(n) => n,
+                None => return None,
+            };
+
+            // Prefer the linked version if several are installed
+            let version = f.get("linked_keg").and_then(|v| v.as_str()).or_else(|| {
+                f.get("installed").and_then(|i| i.as_array()).and_then(|i| i.last()).and_then(|i| i.get("version")).and_then(|v| v.as_str())
+            });
+
+            version.map(|v| InstalledPackage {
+                name: name.to_string(),
+                version: v.to_string(),
+                arch: None,
+                repository: f.get("tap").and_then(|t| t.as_str()).map(|t| t.to_string()),
+            })
+        }).collect())
+    }
+}
+
+fn is_tapped(host: &mut Host, name: &str) -> Result<bool> {
+    let cmd = Command::new("brew tap");
+    let result = try!(cmd.exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    Ok(result.stdout.lines().any(|l| l.trim() == name))
+}
+
+/// Strip a trailing `_N` revision from a Homebrew version.
+fn strip_revision(version: &str) -> &str {
+    match version.rfind('_') {
+        Some(i) if i + 1 < version.len() && version[i + 1..].chars().all(|c| c.is_digit(10)) => &version[..i],
+        _ => version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[test]
+    fn test_strip_revision() {
+        assert_eq!(strip_revision("1.21.1_2"), "1.21.1");
+        assert_eq!(strip_revision("1.21.1"), "1.21.1");
+        assert_eq!(strip_revision("2023_beta"), "2023_beta");
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_is_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("brew list --versions 'nginx'", "nginx 1.21.0 1.21.1\n"),
+            mock::err("brew list --versions 'nginx-full'", 1, ""),
+            mock::ok("brew list --versions 'nginx'", "nginx 1.21.0 1.21.1_2\n"),
+        ]);
+
+        assert!(Homebrew.is_installed(&mut host, "nginx").unwrap());
+        assert!(!Homebrew.is_installed(&mut host, "nginx-full").unwrap());
+        assert_eq!(Homebrew.installed_version(&mut host, "nginx").unwrap(), Some("1.21.1".into()));
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_pending_upgrades() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("brew outdated --verbose", "openssl@1.1 (1.1.1k, 1.1.1l) < 1.1.1m\nnginx (1.21.0) < 1.21.1\n"),
+        ]);
+
+        let pending = Homebrew.pending_upgrades(&mut host, false).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0], PendingUpgrade {
+            name: "openssl@1.1".into(),
+            current_version: Some("1.1.1l".into()),
+            new_version: "1.1.1m".into(),
+        });
+
+        agent_mock.join().unwrap();
+    }
 }
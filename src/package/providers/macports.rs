@@ -8,35 +8,212 @@
 
 //! Macports package provider
 
-use command::CommandResult;
-use error::Result;
+use command::{Command, CommandResult};
+use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
 use super::*;
 
 pub struct Macports;
 
+impl Macports {
+    /// Get the name and version of each active port.
+    fn active(&self, host: &mut Host) -> Result<Vec<(String, String)>> {
+        let cmd = Command::new("port -q installed active");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_installed(&result.stdout))
+    }
+}
+
 impl Provider for Macports {
     fn get_providers(&self) -> Providers {
         Providers::Macports
     }
 
-    #[allow(unused_variables)]
     fn is_active(&self, host: &mut Host) -> Result<bool> {
-        Ok(false)
+        let cmd = Command::new("type port");
+        let result = try!(cmd.exec(host));
+
+        Ok(result.exit_code == 0)
     }
 
-    #[allow(unused_variables)]
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        unimplemented!();
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    /// The port's revision is dropped so that the version can be
+    /// compared with `candidate_version`.
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("port -q installed {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        // Exits non-zero if the port doesn't exist
+        if result.exit_code != 0 {
+            return Ok(None);
+        }
+
+        Ok(parse_installed(&result.stdout).into_iter()
+                                          .find(|&(ref n, _)| n == name)
+                                          .map(|(_, v)| v))
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("port -q info --version {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        Ok(if result.exit_code == 0 { result.stdout.split_whitespace().last().map(|v| v.to_string()) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let installed = try!(self.active(host));
+
+        Ok(names.iter()
+                .filter(|n| installed.iter().any(|&(ref name, _)| name.as_str() == **n))
+                .map(|n| n.to_string())
+                .collect())
     }
 
-    #[allow(unused_variables)]
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
-        unimplemented!();
+        let cmd = Command::new(&format!("port -N install {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    /// The ports tree only has one version of each port, so this fails
+    /// unless that version satisfies `version`.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        match try!(self.candidate_version(host, name)) {
+            Some(ref candidate) if version_matches(version, candidate) => self.install(host, name),
+            Some(candidate) => Err(Error::Generic(format!("The ports tree has {} {}, which does not match {}", name, candidate, version))),
+            None => Err(Error::Generic(format!("Could not find a port named {}", name))),
+        }
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("port -N install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("port -N upgrade {}", quote(name)));
+        cmd.exec(host)
     }
 
-    #[allow(unused_variables)]
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
-        unimplemented!();
+        let cmd = Command::new(&format!("port -N uninstall {}", quote(name)));
+        cmd.exec(host)
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Macports does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Macports does not support repositories".into()))
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("port -q sync");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Macports does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("port -q outdated");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_outdated(&result.stdout))
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        // Upgrading "outdated" fails if nothing matches
+        if try!(self.pending_upgrades(host, security_only)).is_empty() {
+            return Ok(no_op());
+        }
+
+        let cmd = Command::new("port -N upgrade outdated");
+        cmd.exec(host)
+    }
+
+    /// Only active ports are listed, as inactive versions aren't in
+    /// use.
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        Ok(try!(self.active(host)).into_iter().map(|(name, version)| InstalledPackage {
+            name: name,
+            version: version,
+            arch: None,
+            repository: None,
+        }).collect())
+    }
+}
+
+/// Parse the active ports in `port installed` output, e.g.
+/// `  nginx @1.25.3_0+ssl (active)`. Variants and the revision are
+/// dropped from the version.
+fn parse_installed(output: &str) -> Vec<(String, String)> {
+    output.lines().filter_map(|l| {
+        let parts: Vec<&str> = l.split_whitespace().collect();
+        if parts.len() == 3 && parts[2] == "(active)" && parts[1].starts_with('@') {
+            let version = parts[1][1..].split('+').next().unwrap_or("");
+            let version = match version.rfind('_') {
+                Some(i) => &version[..i],
+                None => version,
+            };
+            Some((parts[0].to_string(), version.to_string()))
+        } else {
+            None
+        }
+    }).collect()
+}
+
+/// Parse `port outdated` output, e.g.
+/// `nginx                          1.25.2_0 < 1.25.3_0`.
+fn parse_outdated(output: &str) -> Vec<PendingUpgrade> {
+    output.lines().filter_map(|l| {
+        let parts: Vec<&str> = l.split_whitespace().collect();
+        if parts.len() >= 4 && parts[2] == "<" {
+            Some(PendingUpgrade {
+                name: parts[0].to_string(),
+                current_version: Some(parts[1].to_string()),
+                new_version: parts[3].to_string(),
+            })
+        } else {
+            None
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_installed() {
+        let output = "  nginx @1.25.3_0+ssl (active)\n  zlib @1.3_0\n  zlib @1.3.1_1 (active)\n";
+
+        assert_eq!(parse_installed(output), vec![
+            ("nginx".to_string(), "1.25.3".to_string()),
+            ("zlib".to_string(), "1.3.1".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_outdated() {
+        assert_eq!(parse_outdated("nginx                          1.25.2_0 < 1.25.3_0\n"), vec![PendingUpgrade {
+            name: "nginx".into(),
+            current_version: Some("1.25.2_0".into()),
+            new_version: "1.25.3_0".into(),
+        }]);
     }
 }
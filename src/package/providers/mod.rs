@@ -7,22 +7,28 @@
 // modified, or distributed except according to those terms.
 
 pub mod apt;
+pub mod cargo;
 pub mod dnf;
+pub mod gem;
 pub mod homebrew;
 pub mod macports;
+pub mod npm;
+pub mod pip;
 pub mod pkg;
 pub mod ports;
 pub mod yum;
 pub mod nix;
 
-use command::CommandResult;
+use command::{Command, CommandResult};
 use error::{Error, Result};
+use file::{File, FileContent, FileSpec};
+use glob::Pattern;
 use host::Host;
 pub use self::homebrew::Homebrew;
 use std::convert;
 use std::panic;
 use std::string::ToString;
-use super::PackageTarget;
+use super::{InstalledPackage, PackageTarget, PendingUpgrade, RepositorySpec};
 use target::Target;
 
 /// Supported package providers.
@@ -35,8 +41,20 @@ pub enum Providers {
     Ports,
     Yum,
     Nix,
+    /// Python packages, installed into the virtualenv at the given
+    /// path, or system-wide if `None`
+    Pip(Option<String>),
+    /// Ruby gems
+    Gem,
+    /// Node packages, installed into the given project directory, or
+    /// globally if `None`
+    Npm(Option<String>),
+    /// Rust binaries installed with `cargo install`
+    Cargo,
 }
 
+/// Providers that carry a path are written as `<name>:<path>`, e.g.
+/// `Pip:/srv/app/venv`, so that `From<String>` can restore them.
 impl ToString for Providers {
     fn to_string(&self) -> String {
         match self {
@@ -48,21 +66,36 @@ impl ToString for Providers {
             &Providers::Ports => "Ports".to_string(),
             &Providers::Yum => "Yum".to_string(),
             &Providers::Nix => "Nix".to_string(),
+            &Providers::Pip(Some(ref virtualenv)) => format!("Pip:{}", virtualenv),
+            &Providers::Pip(None) => "Pip".to_string(),
+            &Providers::Gem => "Gem".to_string(),
+            &Providers::Npm(Some(ref directory)) => format!("Npm:{}", directory),
+            &Providers::Npm(None) => "Npm".to_string(),
+            &Providers::Cargo => "Cargo".to_string(),
         }
     }
 }
 
 impl convert::From<String> for Providers {
     fn from(provider: String) -> Providers {
-        match provider.as_ref() {
-            "Apt" => Providers::Apt,
-            "Dnf" => Providers::Dnf,
-            "Homebrew" => Providers::Homebrew,
-            "Macports" => Providers::Macports,
-            "Pkg" => Providers::Pkg,
-            "Ports" => Providers::Ports,
-            "Yum" => Providers::Yum,
-            "Nix" => Providers::Nix,
+        let (name, path) = match provider.find(':') {
+            Some(i) => (&provider[..i], Some(provider[i + 1..].to_string())),
+            None => (provider.as_ref(), None),
+        };
+
+        match (name, path) {
+            ("Pip", path) => Providers::Pip(path),
+            ("Npm", path) => Providers::Npm(path),
+            ("Apt", None) => Providers::Apt,
+            ("Dnf", None) => Providers::Dnf,
+            ("Homebrew", None) => Providers::Homebrew,
+            ("Macports", None) => Providers::Macports,
+            ("Pkg", None) => Providers::Pkg,
+            ("Ports", None) => Providers::Ports,
+            ("Yum", None) => Providers::Yum,
+            ("Nix", None) => Providers::Nix,
+            ("Gem", None) => Providers::Gem,
+            ("Cargo", None) => Providers::Cargo,
             _ => panic!("Invalid provider"),
         }
     }
@@ -97,6 +130,10 @@ impl ProviderFactory {
             Providers::Ports => Box::new(ports::Ports),
             Providers::Yum => Box::new(yum::Yum),
             Providers::Nix => Box::new(nix::Nix),
+            Providers::Pip(virtualenv) => Box::new(pip::Pip::new(virtualenv)),
+            Providers::Gem => Box::new(gem::Gem),
+            Providers::Npm(directory) => Box::new(npm::Npm::new(directory)),
+            Providers::Cargo => Box::new(cargo::Cargo),
         }
     }
 }
@@ -105,6 +142,269 @@ pub trait Provider: panic::UnwindSafe {
     fn get_providers(&self) -> Providers;
     fn is_active(&self, host: &mut Host) -> Result<bool>;
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool>;
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>>;
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>>;
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult>;
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>>;
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult>;
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult>;
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult>;
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult>;
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool>;
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool>;
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult>;
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>>;
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult>;
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>>;
+}
+
+/// Check whether a version satisfies a version requirement.
+///
+/// A requirement is either an exact version, e.g. `1.18.0-1`, or a
+/// glob matching a range of versions, e.g. `1.18.*`.
+pub fn version_matches(requirement: &str, version: &str) -> bool {
+    match Pattern::new(requirement) {
+        Ok(pattern) => pattern.matches(version),
+        Err(_) => requirement == version,
+    }
+}
+
+/// Result for operations that a provider doesn't need to run, such as
+/// refreshing a cache that doesn't exist.
+fn no_op() -> CommandResult {
+    CommandResult {
+        exit_code: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+    }
+}
+
+/// Write a config file if its contents differ, returning `true` if it
+/// changed.
+fn ensure_file(host: &mut Host, path: &str, content: String) -> Result<bool> {
+    let file = try!(File::new(host, path));
+    let changes = try!(file.ensure(host, FileSpec {
+        content: Some(FileContent::Bytes(content.into_bytes())),
+        mode: Some(644),
+        ..Default::default()
+    }));
+    Ok(!changes.is_empty())
+}
+
+/// Delete a file if it exists, returning `true` if it did.
+fn remove_file(host: &mut Host, path: &str) -> Result<bool> {
+    let file = try!(File::new(host, path));
+    if try!(file.exists(host)) {
+        try!(file.delete(host));
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Download a signing key to `path` with `fetch_cmd`, e.g. `curl -fsSLo`,
+/// unless it already exists. Returns `true` if the key was
+/// downloaded.
+fn fetch_key(host: &mut Host, fetch_cmd: &str, url: &str, path: &str) -> Result<bool> {
+    if try!(try!(File::new(host, path)).exists(host)) {
+        return Ok(false);
+    }
+
+    let dir = path.rsplitn(2, '/').last().unwrap();
+    let cmd = Command::new(&format!("mkdir -p {} && {} {} {}", quote(dir), fetch_cmd, quote(path), quote(url)));
+    let result = try!(cmd.exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    Ok(true)
+}
+
+/// Write a yum/dnf `.repo` file.
+fn add_rpm_repository(host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+    let url = try!(spec.url.as_ref().ok_or(Error::Generic("Yum and Dnf repositories require a URL".into())));
+
+    let gpg = match spec.key_url {
+        Some(ref key_url) => format!("gpgcheck=1\ngpgkey={}\n", key_url),
+        None => "gpgcheck=0\n".to_string(),
+    };
+    let conf = format!("[{}]\nname={}\nbaseurl={}\nenabled=1\n{}", name, name, url, gpg);
+
+    ensure_file(host, &format!("/etc/yum.repos.d/{}.repo", name), conf)
+}
+
+/// Quote a command argument for the shell.
+pub fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace("'", "'\\''"))
+}
+
+/// Quote each argument and join them with spaces.
+fn quote_all(args: &[&str]) -> String {
+    args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" ")
+}
+
+/// Parse the newest version of a package from `yum list` or
+/// `dnf list` output, stripping any epoch so that it compares equal
+/// to `rpm -q` output.
+fn parse_rpm_list(output: &str, name: &str) -> Option<String> {
+    output.lines()
+          .filter_map(|line| {
+              let mut cols = line.split_whitespace();
+              match (cols.next(), cols.next()) {
+                  (Some(pkg), Some(version)) if pkg.rsplitn(2, '.').last() == Some(name) => {
+                      Some(version.splitn(2, ':').last().unwrap().to_string())
+                  },
+                  _ => None,
+              }
+          })
+          .last()
+}
+
+/// Parse `yum check-update` or `dnf check-update` output.
+///
+/// As with `parse_list_installed`, long lines are wrapped, so this
+/// reads `<name>.<arch> <version> <repo>` triples from a token stream.
+fn parse_check_update(output: &str) -> Vec<PendingUpgrade> {
+    let tokens: Vec<&str> = output.lines()
+                                  // Packages listed after this heading
+                                  // replace others, which are already
+                                  // listed above.
+                                  .take_while(|l| !l.starts_with("Obsoleting Packages"))
+                                  .flat_map(|l| l.split_whitespace())
+                                  .collect();
+
+    tokens.chunks(3)
+          .filter(|c| c.len() == 3 && c[0].contains('.'))
+          .map(|c| PendingUpgrade {
+              name: c[0].rsplitn(2, '.').last().unwrap().to_string(),
+              current_version: None,
+              new_version: c[1].splitn(2, ':').last().unwrap().to_string(),
+          })
+          .collect()
+}
+
+/// Run `check-update` for yum or dnf, which exits with 100 if there
+/// are upgrades available.
+fn rpm_pending_upgrades(host: &mut Host, tool: &str, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+    let cmd = Command::new(&format!("{} -q{} check-update", tool, if security_only { " --security" } else { "" }));
+    let result = try!(cmd.exec(host));
+
+    match result.exit_code {
+        0 => Ok(Vec::new()),
+        100 => Ok(parse_check_update(&result.stdout)),
+        _ => Err(Error::Agent(result.stderr)),
+    }
+}
+
+/// Parse `yum list installed` or `dnf list --installed` output.
+///
+/// Long lines are wrapped when output isn't a terminal, so this reads
+/// the output as a stream of `<name>.<arch> <version> @<repo>`
+/// triples rather than line by line.
+fn parse_list_installed(output: &str) -> Vec<InstalledPackage> {
+    let tokens: Vec<&str> = output.lines()
+                                  .skip_while(|l| !l.starts_with("Installed Packages"))
+                                  .skip(1)
+                                  .flat_map(|l| l.split_whitespace())
+                                  .collect();
+
+    tokens.chunks(3)
+          .filter(|c| c.len() == 3)
+          .map(|c| {
+              let mut name_arch = c[0].rsplitn(2, '.');
+              let arch = name_arch.next().unwrap();
+              let name = name_arch.next().unwrap_or(arch);
+
+              InstalledPackage {
+                  name: name.to_string(),
+                  version: c[1].splitn(2, ':').last().unwrap().to_string(),
+                  arch: if name == arch { None } else { Some(arch.to_string()) },
+                  repository: Some(c[2].trim_left_matches('@').to_string()),
+              }
+          })
+          .collect()
+}
+
+/// Parse the first line of `rpm -q --qf` output, if the package is
+/// installed.
+fn parse_rpm_query(result: CommandResult) -> Option<String> {
+    if result.exit_code == 0 {
+        result.stdout.lines().next().map(|v| v.trim().to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_providers_string() {
+        for s in &["Apt", "Pip", "Pip:/srv/app/venv", "Npm:/srv/app", "Cargo"] {
+            assert_eq!(Providers::from(s.to_string()).to_string(), *s);
+        }
+    }
+
+    #[test]
+    fn test_version_matches() {
+        assert!(version_matches("1.18.0-1", "1.18.0-1"));
+        assert!(!version_matches("1.18.0-1", "1.18.0-2"));
+        assert!(version_matches("1.18.*", "1.18.0-1"));
+        assert!(!version_matches("1.18.*", "1.19.0-1"));
+    }
+
+    #[test]
+    fn test_parse_rpm_list() {
+        let output = "Installed Packages\n\
+                      nginx.x86_64          1:1.20.1-1.el7          @epel\n\
+                      Available Packages\n\
+                      nginx.x86_64          1:1.20.2-1.el7          epel\n\
+                      nginx-mod-mail.x86_64 1:1.20.2-1.el7          epel\n";
+        assert_eq!(parse_rpm_list(output, "nginx"), Some("1.20.2-1.el7".into()));
+        assert_eq!(parse_rpm_list(output, "httpd"), None);
+    }
+
+    #[test]
+    fn test_parse_list_installed() {
+        let output = "Installed Packages\n\
+                      bash.x86_64                 4.2.46-34.el7          @base\n\
+                      python-backports-ssl_match_hostname.noarch\n\
+                      \x20                           3.5.0.1-1.el7          @base\n\
+                      nginx.x86_64                1:1.20.1-10.el7        @epel\n";
+        let installed = parse_list_installed(output);
+
+        assert_eq!(installed.len(), 3);
+        assert_eq!(installed[1].name, "python-backports-ssl_match_hostname");
+        assert_eq!(installed[2], InstalledPackage {
+            name: "nginx".into(),
+            version: "1.20.1-10.el7".into(),
+            arch: Some("x86_64".into()),
+            repository: Some("epel".into()),
+        });
+    }
+
+    #[test]
+    fn test_parse_check_update() {
+        let output = "\n\
+                      bash.x86_64                 4.2.46-35.el7_9        updates\n\
+                      kernel.x86_64               3.10.0-1160.el7        updates\n\
+                      nginx.x86_64                1:1.20.2-1.el7         epel\n\
+                      python3-setuptools-wheel.noarch\n\
+                      \x20                           39.2.0-10.el7_9        updates\n\
+                      Obsoleting Packages\n\
+                      grub2.x86_64                1:2.02-0.87.el7        updates\n";
+        let pending = parse_check_update(output);
+        assert_eq!(pending.len(), 4);
+        assert_eq!(pending[2], PendingUpgrade {
+            name: "nginx".into(),
+            current_version: None,
+            new_version: "1.20.2-1.el7".into(),
+        });
+        assert_eq!(pending[3], PendingUpgrade {
+            name: "python3-setuptools-wheel".into(),
+            current_version: None,
+            new_version: "39.2.0-10.el7_9".into(),
+        });
+    }
 }
@@ -9,8 +9,12 @@
 //! Nix package provider
 
 use command::{Command, CommandResult};
-use error::Result;
+use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use regex::Regex;
+use serde_json;
+use std::collections::BTreeMap;
 use super::*;
 
 pub struct Nix;
@@ -28,14 +32,35 @@ impl Provider for Nix {
     }
 
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        let cmd = Command::new(&format!("nix-env --install --dry-run {}", name));
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("nix-env --query --installed {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        // Exits non-zero if the package isn't installed
+        Ok(if result.exit_code == 0 { parse_nix_query(&result.stdout, name) } else { None })
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("nix-env --query --available {}", quote(name)));
         let result = try!(cmd.exec(host));
 
+        Ok(if result.exit_code == 0 { parse_nix_query(&result.stdout, name) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new("nix-env --query --installed");
+        let result = try!(cmd.exec(host));
         if result.exit_code != 0 {
             return Err(Error::Agent(result.stderr));
         }
 
-        Ok(!result.stderr.contains("these paths will be fetched"))
+        Ok(names.iter()
+                .filter(|n| parse_nix_query(&result.stdout, n).is_some())
+                .map(|n| n.to_string())
+                .collect())
     }
 
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
@@ -43,8 +68,191 @@ impl Provider for Nix {
         cmd.exec(host)
     }
 
+    /// Nix selects packages by exact name and version, so version
+    /// ranges are not supported.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        if version.contains(|c: char| c == '*' || c == '?' || c == '[') {
+            return Err(Error::Generic("Nix does not support version ranges".into()));
+        }
+
+        let cmd = Command::new(&format!("nix-env --install {}", quote(&format!("{}-{}", name, version))));
+        cmd.exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("nix-env --install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("nix-env --upgrade {}", quote(name)));
+        cmd.exec(host)
+    }
+
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("nix-env --uninstall {}", name));
         cmd.exec(host)
     }
+
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        let url = try!(spec.url.as_ref().ok_or(Error::Generic("Nix channels require a URL".into())));
+
+        if try!(channel_url(host, name)).as_ref() == Some(url) {
+            return Ok(false);
+        }
+
+        // Adding an existing channel replaces its URL
+        let cmd = Command::new(&format!("nix-channel --add {} {}", quote(url), quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(true)
+    }
+
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        if try!(channel_url(host, name)).is_none() {
+            return Ok(false);
+        }
+
+        let cmd = Command::new(&format!("nix-channel --remove {}", quote(name)));
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(true)
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("nix-channel --update");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Nix does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("nix-env --upgrade --dry-run");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        // Upgrades are reported on stderr as
+        // `upgrading 'hello-2.10' to 'hello-2.12'`
+        let re = try!(Regex::new(r"upgrading '(.+?)' to '(.+?)'"));
+        Ok(re.captures_iter(&result.stderr).filter_map(|cap| {
+            let (name, current) = match split_name_version(&cap[1]) {
+                Some(nv) => nv,
+                None => return None,
+            };
+            split_name_version(&cap[2]).map(|(_, new)| PendingUpgrade {
+                name: name.to_string(),
+                current_version: Some(current.to_string()),
+                new_version: new.to_string(),
+            })
+        }).collect())
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        if security_only {
+            return Err(Error::Generic("Nix does not support security-only upgrades".into()));
+        }
+
+        let cmd = Command::new("nix-env --upgrade");
+        cmd.exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("nix-env --query --installed --json");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let packages: BTreeMap<String, serde_json::Value> = try!(serde_json::from_str(&result.stdout));
+        Ok(packages.values().filter_map(|p| {
+            p.get("name").and_then(|n| n.as_str()).and_then(|n| split_name_version(n)).map(|(name, version)| InstalledPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                arch: p.get("system").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                repository: None,
+            })
+        }).collect())
+    }
+}
+
+/// Parse the version from `nix-env --query` output, which names
+/// packages as `<name>-<version>`. If several versions are listed,
+/// the newest is last.
+fn parse_nix_query(output: &str, name: &str) -> Option<String> {
+    let prefix = format!("{}-", name);
+    output.lines()
+          .map(|l| l.trim())
+          .filter(|l| l.starts_with(&prefix))
+          .map(|l| &l[prefix.len()..])
+          // Versions start with a digit, which distinguishes `hello`
+          // from `hello-wayland`.
+          .filter(|v| v.chars().next().map_or(false, |c| c.is_digit(10)))
+          .map(|v| v.to_string())
+          .last()
+}
+
+/// Get the URL of a channel, or `None` if it doesn't exist.
+fn channel_url(host: &mut Host, name: &str) -> Result<Option<String>> {
+    let cmd = Command::new("nix-channel --list");
+    let result = try!(cmd.exec(host));
+    if result.exit_code != 0 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    Ok(result.stdout.lines()
+                    .filter_map(|l| {
+                        let mut cols = l.split_whitespace();
+                        match (cols.next(), cols.next()) {
+                            (Some(n), Some(url)) if n == name => Some(url.to_string()),
+                            _ => None,
+                        }
+                    })
+                    .next())
+}
+
+/// Split a Nix package name such as `hello-2.10` into its name and
+/// version, which starts at the first dash followed by a digit.
+fn split_name_version(package: &str) -> Option<(&str, &str)> {
+    package.match_indices('-')
+           .map(|(i, _)| i)
+           .find(|&i| package[i + 1..].chars().next().map_or(false, |c| c.is_digit(10)))
+           .map(|i| (&package[..i], &package[i + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_is_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("nix-env --query --installed 'hello'", "hello-2.10\n"),
+            mock::err("nix-env --query --installed 'hello-wayland'", 1, ""),
+        ]);
+
+        assert!(Nix.is_installed(&mut host, "hello").unwrap());
+        assert!(!Nix.is_installed(&mut host, "hello-wayland").unwrap());
+
+        agent_mock.join().unwrap();
+    }
+
+    #[test]
+    fn test_split_name_version() {
+        assert_eq!(split_name_version("hello-2.10"), Some(("hello", "2.10")));
+        assert_eq!(split_name_version("hello-wayland-0.1"), Some(("hello-wayland", "0.1")));
+        assert_eq!(split_name_version("hello"), None);
+    }
 }
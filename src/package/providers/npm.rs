@@ -0,0 +1,230 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Npm package provider
+
+use command::{Command, CommandResult};
+use error::{Error, Result};
+use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use serde_json;
+use std::collections::BTreeMap;
+use super::*;
+
+pub struct Npm {
+    /// Project directory, or `None` for global packages
+    directory: Option<String>,
+}
+
+impl Npm {
+    pub fn new(directory: Option<String>) -> Npm {
+        Npm {
+            directory: directory,
+        }
+    }
+
+    /// Build an npm command that operates on the project directory, or
+    /// on global packages.
+    fn npm(&self, args: &str) -> Command {
+        match self.directory {
+            Some(ref path) => Command::new(&format!("npm {} --prefix {}", args, quote(path))),
+            None => Command::new(&format!("npm {} --global", args)),
+        }
+    }
+
+    /// Get the version of each top-level package.
+    fn list(&self, host: &mut Host) -> Result<BTreeMap<String, String>> {
+        let result = try!(self.npm("ls --depth=0 --json").exec(host));
+
+        // Exits non-zero if any dependencies are missing or invalid,
+        // but still lists what is installed.
+        let tree: serde_json::Value = match serde_json::from_str(&result.stdout) {
+            Ok(tree) => tree,
+            Err(_) if result.exit_code != 0 => return Err(Error::Agent(result.stderr)),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut packages = BTreeMap::new();
+        if let Some(deps) = tree.get("dependencies").and_then(|d| d.as_object()) {
+            for (name, dep) in deps {
+                if let Some(version) = dep.get("version").and_then(|v| v.as_str()) {
+                    packages.insert(name.clone(), version.to_string());
+                }
+            }
+        }
+
+        Ok(packages)
+    }
+}
+
+impl Provider for Npm {
+    fn get_providers(&self) -> Providers {
+        Providers::Npm(self.directory.clone())
+    }
+
+    fn is_active(&self, host: &mut Host) -> Result<bool> {
+        let cmd = Command::new("type npm");
+        let result = try!(cmd.exec(host));
+
+        Ok(result.exit_code == 0)
+    }
+
+    fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        Ok(try!(self.list(host)).remove(name))
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("npm view {} version", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        let version = result.stdout.trim();
+        Ok(if result.exit_code == 0 && !version.is_empty() { Some(version.to_string()) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let installed = try!(self.list(host));
+
+        Ok(names.iter()
+                .filter(|n| installed.contains_key(**n))
+                .map(|n| n.to_string())
+                .collect())
+    }
+
+    fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.npm(&format!("install {}", quote(name))).exec(host)
+    }
+
+    /// Npm understands globs such as `1.18.*` as version ranges.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        if version.contains(|c: char| c == '?' || c == '[') {
+            return Err(Error::Generic("Npm only supports version ranges using `*`".into()));
+        }
+
+        self.npm(&format!("install {}", quote(&format!("{}@{}", name, version)))).exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        self.npm(&format!("install {}", quote_all(names))).exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.npm(&format!("install {}", quote(&format!("{}@latest", name)))).exec(host)
+    }
+
+    fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.npm(&format!("uninstall {}", quote(name))).exec(host)
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Npm does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Npm does not support repositories".into()))
+    }
+
+    /// Npm queries the registry directly, so has no cache.
+    #[allow(unused_variables)]
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        Ok(no_op())
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Npm does not support security-only upgrades".into()));
+        }
+
+        // Exits 1 if anything is outdated
+        let result = try!(self.npm("outdated --json").exec(host));
+        if result.exit_code > 1 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        if result.stdout.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let outdated: BTreeMap<String, serde_json::Value> = try!(serde_json::from_str(&result.stdout));
+        Ok(outdated.into_iter().filter_map(|(name, info)| {
+            info.get("latest").and_then(|v| v.as_str()).map(|latest| PendingUpgrade {
+                name: name.clone(),
+                current_version: info.get("current").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                new_version: latest.to_string(),
+            })
+        }).collect())
+    }
+
+    /// `npm update` stays within the ranges in `package.json`, so each
+    /// outdated package is installed at its latest version instead.
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let pending = try!(self.pending_upgrades(host, security_only));
+        if pending.is_empty() {
+            return Ok(no_op());
+        }
+
+        let latest: Vec<String> = pending.iter().map(|p| format!("{}@latest", p.name)).collect();
+        let latest: Vec<&str> = latest.iter().map(|l| l.as_str()).collect();
+        self.npm(&format!("install {}", quote_all(&latest))).exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        Ok(try!(self.list(host)).into_iter().map(|(name, version)| InstalledPackage {
+            name: name,
+            version: version,
+            arch: None,
+            repository: None,
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    #[cfg(feature = "remote-run")]
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_directory() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::err("npm ls --depth=0 --json --prefix '/srv/app'", 1, "{\"name\": \"app\", \"dependencies\": {\
+                                                                         \"express\": {\"version\": \"4.18.2\"},\
+                                                                         \"left-pad\": {\"missing\": true}}}"),
+            mock::ok("npm install 'express@4.19.*' --prefix '/srv/app'", ""),
+        ]);
+
+        let npm = Npm::new(Some("/srv/app".into()));
+        assert_eq!(npm.installed_many(&mut host, &["express", "left-pad"]).unwrap(), vec!["express".to_string()]);
+        assert_eq!(npm.install_version(&mut host, "express", "4.19.*").unwrap().exit_code, 0);
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_pending_upgrades() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::err("npm outdated --json --global", 1, "{\"typescript\": {\"current\": \"5.2.2\", \"wanted\": \"5.3.3\", \"latest\": \"5.3.3\"}}"),
+        ]);
+
+        assert_eq!(Npm::new(None).pending_upgrades(&mut host, false).unwrap(), vec![PendingUpgrade {
+            name: "typescript".into(),
+            current_version: Some("5.2.2".into()),
+            new_version: "5.3.3".into(),
+        }]);
+
+        agent_mock.join().unwrap();
+    }
+}
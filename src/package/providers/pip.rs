@@ -0,0 +1,228 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Pip package provider
+
+use command::{Command, CommandResult};
+use error::{Error, Result};
+use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use serde_json;
+use super::*;
+
+pub struct Pip {
+    /// Path to a virtualenv, or `None` for the system's pip
+    virtualenv: Option<String>,
+}
+
+impl Pip {
+    pub fn new(virtualenv: Option<String>) -> Pip {
+        Pip {
+            virtualenv: virtualenv,
+        }
+    }
+
+    /// Build a pip command, using the virtualenv's pip if there is one.
+    fn pip(&self, args: &str) -> Command {
+        match self.virtualenv {
+            Some(ref path) => Command::new(&format!("{} {}", quote(&format!("{}/bin/pip", path.trim_right_matches('/'))), args)),
+            None => Command::new(&format!("pip {}", args)),
+        }
+    }
+
+    /// Get the name and version of every installed package.
+    fn list(&self, host: &mut Host, args: &str) -> Result<Vec<serde_json::Value>> {
+        let result = try!(self.pip(&format!("list --format=json{}", args)).exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(try!(serde_json::from_str(&result.stdout)))
+    }
+}
+
+impl Provider for Pip {
+    fn get_providers(&self) -> Providers {
+        Providers::Pip(self.virtualenv.clone())
+    }
+
+    fn is_active(&self, host: &mut Host) -> Result<bool> {
+        let result = try!(self.pip("--version").exec(host));
+
+        Ok(result.exit_code == 0)
+    }
+
+    fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let result = try!(self.pip(&format!("show {}", quote(name))).exec(host));
+
+        // Exits non-zero if the package isn't installed
+        if result.exit_code == 0 {
+            Ok(result.stdout.lines()
+                            .find(|l| l.starts_with("Version:"))
+                            .map(|l| l["Version:".len()..].trim().to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let result = try!(self.pip(&format!("index versions {}", quote(name))).exec(host));
+
+        // First line is the name followed by the latest version, e.g.
+        // `requests (2.31.0)`.
+        if result.exit_code == 0 {
+            Ok(result.stdout.lines().next().and_then(|l| {
+                let start = l.find('(');
+                let end = l.find(')');
+                match (start, end) {
+                    (Some(s), Some(e)) if s < e => Some(l[s + 1..e].to_string()),
+                    _ => None,
+                }
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let installed: Vec<String> = try!(self.list(host, "")).iter()
+                                                              .filter_map(|p| p.get("name").and_then(|n| n.as_str()))
+                                                              .map(|n| normalise_name(n))
+                                                              .collect();
+
+        Ok(names.iter()
+                .filter(|n| installed.contains(&normalise_name(n)))
+                .map(|n| n.to_string())
+                .collect())
+    }
+
+    fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.pip(&format!("install {}", quote(name))).exec(host)
+    }
+
+    /// Globs ending in `.*`, e.g. `1.18.*`, are passed to pip as a
+    /// wildcard requirement.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        if version.contains(|c: char| c == '?' || c == '[') || version.trim_right_matches(".*").contains('*') {
+            return Err(Error::Generic("Pip only supports version ranges ending in `.*`".into()));
+        }
+
+        self.pip(&format!("install {}", quote(&format!("{}=={}", name, version)))).exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        self.pip(&format!("install {}", quote_all(names))).exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.pip(&format!("install --upgrade {}", quote(name))).exec(host)
+    }
+
+    fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.pip(&format!("uninstall -y {}", quote(name))).exec(host)
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Pip does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Pip does not support repositories".into()))
+    }
+
+    /// Pip queries the package index directly, so has no cache.
+    #[allow(unused_variables)]
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        Ok(no_op())
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        if security_only {
+            return Err(Error::Generic("Pip does not support security-only upgrades".into()));
+        }
+
+        Ok(try!(self.list(host, " --outdated")).iter().filter_map(|p| {
+            match (p.get("name").and_then(|n| n.as_str()), p.get("latest_version").and_then(|v| v.as_str())) {
+                (Some(name), Some(latest)) => Some(PendingUpgrade {
+                    name: name.to_string(),
+                    current_version: p.get("version").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                    new_version: latest.to_string(),
+                }),
+                _ => None,
+            }
+        }).collect())
+    }
+
+    /// Pip can't upgrade everything at once, so each outdated package
+    /// is upgraded explicitly.
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let pending = try!(self.pending_upgrades(host, security_only));
+        if pending.is_empty() {
+            return Ok(no_op());
+        }
+
+        let names: Vec<&str> = pending.iter().map(|p| p.name.as_str()).collect();
+        self.pip(&format!("install --upgrade {}", quote_all(&names))).exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        Ok(try!(self.list(host, "")).iter().filter_map(|p| {
+            match (p.get("name").and_then(|n| n.as_str()), p.get("version").and_then(|v| v.as_str())) {
+                (Some(name), Some(version)) => Some(InstalledPackage {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    arch: None,
+                    repository: None,
+                }),
+                _ => None,
+            }
+        }).collect())
+    }
+}
+
+/// Normalise a package name as pip compares them, so `PyYAML` matches
+/// `pyyaml` and `typing_extensions` matches `typing-extensions`.
+fn normalise_name(name: &str) -> String {
+    name.to_lowercase().replace('_', "-").replace('.', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[test]
+    fn test_normalise_name() {
+        assert_eq!(normalise_name("PyYAML"), "pyyaml");
+        assert_eq!(normalise_name("typing_extensions"), "typing-extensions");
+        assert_eq!(normalise_name("zope.interface"), "zope-interface");
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_virtualenv() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("'/srv/app/venv/bin/pip' show 'Django'", "Name: Django\nVersion: 4.2.7\nSummary: A high-level Python web framework.\n"),
+            mock::ok("'/srv/app/venv/bin/pip' install 'Django==4.2.*'", ""),
+        ]);
+
+        let pip = Pip::new(Some("/srv/app/venv/".into()));
+        assert_eq!(pip.installed_version(&mut host, "Django").unwrap(), Some("4.2.7".into()));
+        assert_eq!(pip.install_version(&mut host, "Django", "4.2.*").unwrap().exit_code, 0);
+        assert!(pip.install_version(&mut host, "Django", "4.*.1").is_err());
+
+        agent_mock.join().unwrap();
+    }
+}
@@ -9,8 +9,10 @@
 //! Pkg package provider
 
 use command::{Command, CommandResult};
-use error::Result;
+use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use regex::Regex;
 use super::*;
 
 pub struct Pkg;
@@ -28,19 +30,234 @@ impl Provider for Pkg {
     }
 
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        let cmd = Command::new(&format!("pkg query \"%n\" {}", name));
+        let cmd = Command::new(&format!("pkg info -e {}", quote(name)));
         let result = try!(cmd.exec(host));
 
         Ok(result.exit_code == 0)
     }
 
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("pkg query %v {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        Ok(if result.exit_code == 0 { Some(result.stdout.trim().to_string()) } else { None })
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("pkg rquery %v {}", quote(name)));
+        let result = try!(cmd.exec(host));
+
+        Ok(if result.exit_code == 0 { result.stdout.lines().next().map(|v| v.trim().to_string()) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new(&format!("pkg query %n {}", quote_all(names)));
+        let result = try!(cmd.exec(host));
+
+        Ok(result.stdout.lines().map(|l| l.trim().to_string()).filter(|l| names.contains(&l.as_str())).collect())
+    }
+
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg install {}", name));
         cmd.exec(host)
     }
 
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg install -g {}", quote(&format!("{}-{}", name, version))));
+        cmd.exec(host)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg upgrade {}", quote(name)));
+        cmd.exec(host)
+    }
+
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg delete {}", name));
         cmd.exec(host)
     }
+
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        let url = try!(spec.url.as_ref().ok_or(Error::Generic("Pkg repositories require a URL".into())));
+        let mut changed = false;
+
+        let signature = match spec.key_url {
+            Some(ref key_url) => {
+                let key = format!("/usr/local/etc/pkg/keys/{}.pub", name);
+                changed |= try!(fetch_key(host, "fetch -qo", key_url, &key));
+                format!("  signature_type: \"pubkey\",\n  pubkey: \"{}\",\n", key)
+            },
+            None => "  signature_type: \"none\",\n".to_string(),
+        };
+
+        let conf = format!("{}: {{\n  url: \"{}\",\n{}  enabled: yes\n}}\n", name, url, signature);
+        changed |= try!(ensure_file(host, &format!("/usr/local/etc/pkg/repos/{}.conf", name), conf));
+        Ok(changed)
+    }
+
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        let conf = try!(remove_file(host, &format!("/usr/local/etc/pkg/repos/{}.conf", name)));
+        let key = try!(remove_file(host, &format!("/usr/local/etc/pkg/keys/{}.pub", name)));
+        Ok(conf || key)
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("pkg update");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        let cmd = Command::new("pkg upgrade -n");
+        let result = try!(cmd.exec(host));
+
+        // A dry run exits with 1 if there are upgrades available
+        if result.exit_code > 1 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let re = try!(Regex::new(r"(?m)^\s+(\S+): (\S+) -> (\S+)"));
+        let upgrades = re.captures_iter(&result.stdout).map(|cap| PendingUpgrade {
+            name: cap[1].to_string(),
+            current_version: Some(cap[2].to_string()),
+            new_version: cap[3].to_string(),
+        });
+
+        if security_only {
+            let vulnerable = try!(vulnerable_packages(host));
+            Ok(upgrades.filter(|u| vulnerable.contains(&u.name)).collect())
+        } else {
+            Ok(upgrades.collect())
+        }
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let cmd = if security_only {
+            let names: Vec<String> = try!(self.pending_upgrades(host, true)).into_iter().map(|u| u.name).collect();
+            if names.is_empty() {
+                Command::new("true")
+            } else {
+                let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+                Command::new(&format!("env ASSUME_ALWAYS_YES=YES pkg upgrade {}", quote_all(&names)))
+            }
+        } else {
+            Command::new("env ASSUME_ALWAYS_YES=YES pkg upgrade")
+        };
+        cmd.exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("pkg query '%n\\t%v\\t%q\\t%R'");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(result.stdout.lines().filter_map(|l| {
+            let cols: Vec<&str> = l.split('\t').collect();
+            if cols.len() == 4 {
+                Some(InstalledPackage {
+                    name: cols[0].to_string(),
+                    version: cols[1].to_string(),
+                    arch: Some(cols[2].to_string()),
+                    // Packages installed from a file have no repository
+                    repository: if cols[3] == "unknown-repository" { None } else { Some(cols[3].to_string()) },
+                })
+            } else {
+                None
+            }
+        }).collect())
+    }
+}
+
+/// Get the names of installed packages with known vulnerabilities.
+pub fn vulnerable_packages(host: &mut Host) -> Result<Vec<String>> {
+    // Fetch the vulnerability database if it is missing or stale
+    let cmd = Command::new("pkg audit -Fq");
+    let result = try!(cmd.exec(host));
+
+    // Exits with 1 if any packages are vulnerable
+    if result.exit_code > 1 {
+        return Err(Error::Agent(result.stderr));
+    }
+
+    // Packages are listed as <name>-<version>
+    Ok(result.stdout.lines()
+                    .filter_map(|l| l.trim().rsplitn(2, '-').nth(1))
+                    .map(|n| n.to_string())
+                    .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    #[cfg(feature = "remote-run")]
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_is_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("pkg info -e 'nginx'", ""),
+            mock::err("pkg info -e 'nginx-devel'", 1, ""),
+        ]);
+
+        assert!(Pkg.is_installed(&mut host, "nginx").unwrap());
+        assert!(!Pkg.is_installed(&mut host, "nginx-devel").unwrap());
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_pending_upgrades() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::err("pkg upgrade -n", 1, "Checking integrity... done (0 conflicting)\n\
+                                           The following 2 package(s) will be affected (of 0 checked):\n\n\
+                                           Installed packages to be UPGRADED:\n\
+                                           \tcurl: 7.79.1 -> 7.80.0\n\
+                                           \tsudo: 1.9.8p1 -> 1.9.8p2\n"),
+            mock::err("pkg audit -Fq", 1, "sudo-1.9.8p1\n"),
+        ]);
+
+        let pending = Pkg.pending_upgrades(&mut host, true).unwrap();
+        assert_eq!(pending, vec![PendingUpgrade {
+            name: "sudo".into(),
+            current_version: Some("1.9.8p1".into()),
+            new_version: "1.9.8p2".into(),
+        }]);
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_list_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("pkg query '%n\\t%v\\t%q\\t%R'", "curl\t7.80.0\tFreeBSD:13:amd64\tFreeBSD\n\
+                                                     mytool\t1.0\tFreeBSD:13:*\tunknown-repository\n"),
+        ]);
+
+        assert_eq!(Pkg.list_installed(&mut host).unwrap(), vec![
+            InstalledPackage {
+                name: "curl".into(),
+                version: "7.80.0".into(),
+                arch: Some("FreeBSD:13:amd64".into()),
+                repository: Some("FreeBSD".into()),
+            },
+            InstalledPackage {
+                name: "mytool".into(),
+                version: "1.0".into(),
+                arch: Some("FreeBSD:13:*".into()),
+                repository: None,
+            },
+        ]);
+
+        agent_mock.join().unwrap();
+    }
 }
@@ -7,36 +7,242 @@
 // modified, or distributed except according to those terms.
 
 //! Ports package provider
+//!
+//! Ports are built from the tree in `/usr/ports` and registered in the
+//! same database as binary packages, so installed ports are queried
+//! with `pkg`. Names can be a package name, e.g. `nginx`, or an origin,
+//! e.g. `www/nginx`.
 
-use command::CommandResult;
-use error::Result;
+use command::{Command, CommandResult};
+use error::{Error, Result};
 use host::Host;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
+use regex::Regex;
 use super::*;
+use super::pkg::{Pkg, vulnerable_packages};
 
 pub struct Ports;
 
+impl Ports {
+    /// Get the name, origin and version of each installed package.
+    fn list(&self, host: &mut Host) -> Result<Vec<(String, String, String)>> {
+        let cmd = Command::new("pkg query '%n\\t%o\\t%v'");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(result.stdout.lines().filter_map(|l| {
+            let cols: Vec<&str> = l.split('\t').collect();
+            if cols.len() == 3 {
+                Some((cols[0].to_string(), cols[1].to_string(), cols[2].to_string()))
+            } else {
+                None
+            }
+        }).collect())
+    }
+
+    /// Get the directory in the ports tree that builds a package.
+    fn port_dir(&self, host: &mut Host, name: &str) -> Result<String> {
+        if name.contains('/') {
+            return Ok(format!("/usr/ports/{}", name));
+        }
+
+        // An installed package records its origin, which may differ
+        // from its name, e.g. py39-pip is built by devel/py-pip.
+        let cmd = Command::new(&format!("pkg query %o {} || whereis -qs {}", quote(name), quote(name)));
+        let result = try!(cmd.exec(host));
+
+        match result.stdout.lines().next().map(|l| l.trim()) {
+            Some(dir) if dir.starts_with('/') => Ok(dir.to_string()),
+            Some(origin) if !origin.is_empty() => Ok(format!("/usr/ports/{}", origin)),
+            _ => Err(Error::Generic(format!("Could not find a port named {}", name))),
+        }
+    }
+
+    /// Run a make target for each package's port, stopping at the
+    /// first failure.
+    fn make(&self, host: &mut Host, names: &[&str], target: &str) -> Result<CommandResult> {
+        let mut cmds = Vec::new();
+        for name in names {
+            cmds.push(format!("make -C {} BATCH=yes {}", quote(&try!(self.port_dir(host, name))), target));
+        }
+
+        let cmd = Command::new(&cmds.join(" && "));
+        cmd.exec(host)
+    }
+}
+
 impl Provider for Ports {
     fn get_providers(&self) -> Providers {
         Providers::Ports
     }
 
-    #[allow(unused_variables)]
     fn is_active(&self, host: &mut Host) -> Result<bool> {
-        Ok(false)
+        let cmd = Command::new("test -f /usr/ports/Mk/bsd.port.mk");
+        let result = try!(cmd.exec(host));
+
+        Ok(result.exit_code == 0)
     }
 
-    #[allow(unused_variables)]
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        unimplemented!();
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        Ok(try!(self.list(host)).into_iter()
+                                .find(|&(ref n, ref origin, _)| n == name || origin == name)
+                                .map(|(_, _, v)| v))
+    }
+
+    /// The candidate is the version in the ports tree.
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let dir = match self.port_dir(host, name) {
+            Ok(dir) => dir,
+            Err(_) => return Ok(None),
+        };
+
+        let cmd = Command::new(&format!("make -C {} -V PKGVERSION", quote(&dir)));
+        let result = try!(cmd.exec(host));
+
+        Ok(if result.exit_code == 0 { result.stdout.lines().next().map(|v| v.trim().to_string()) } else { None })
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let installed = try!(self.list(host));
+
+        Ok(names.iter()
+                .filter(|n| installed.iter().any(|&(ref name, ref origin, _)| name.as_str() == **n || origin.as_str() == **n))
+                .map(|n| n.to_string())
+                .collect())
     }
 
-    #[allow(unused_variables)]
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
-        unimplemented!();
+        self.make(host, &[name], "install clean")
+    }
+
+    /// The ports tree only builds one version of each port, so this
+    /// fails unless that version satisfies `version`.
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        match try!(self.candidate_version(host, name)) {
+            Some(ref candidate) if version_matches(version, candidate) => self.install(host, name),
+            Some(candidate) => Err(Error::Generic(format!("The ports tree has {} {}, which does not match {}", name, candidate, version))),
+            None => Err(Error::Generic(format!("Could not find a port named {}", name))),
+        }
+    }
+
+    /// Ports are built one at a time, in the order given.
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        self.make(host, names, "install clean")
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        self.make(host, &[name], "reinstall clean")
     }
 
-    #[allow(unused_variables)]
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
-        unimplemented!();
+        self.make(host, &[name], "deinstall")
+    }
+
+    #[allow(unused_variables)]
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        Err(Error::Generic("Ports does not support repositories".into()))
+    }
+
+    #[allow(unused_variables)]
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        Err(Error::Generic("Ports does not support repositories".into()))
+    }
+
+    /// Updates the ports tree with git if it is a checkout, otherwise
+    /// with portsnap.
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("if [ -d /usr/ports/.git ]; then git -C /usr/ports pull --ff-only; else portsnap --interactive fetch update; fi");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        let cmd = Command::new("pkg version -P -v -l '<'");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        let upgrades = try!(parse_version(&result.stdout)).into_iter();
+
+        if security_only {
+            let vulnerable = try!(vulnerable_packages(host));
+            Ok(upgrades.filter(|u| vulnerable.contains(&u.name)).collect())
+        } else {
+            Ok(upgrades.collect())
+        }
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let pending = try!(self.pending_upgrades(host, security_only));
+        if pending.is_empty() {
+            return Ok(no_op());
+        }
+
+        let names: Vec<&str> = pending.iter().map(|p| p.name.as_str()).collect();
+        self.make(host, &names, "reinstall clean")
+    }
+
+    /// Lists every package in the package database, including any
+    /// installed from binary packages.
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        Pkg.list_installed(host)
+    }
+}
+
+/// Parse `pkg version -v` output for outdated packages, e.g.
+/// `nginx-1.24.0_1    <   needs updating (port has 1.24.0_2)`.
+fn parse_version(output: &str) -> Result<Vec<PendingUpgrade>> {
+    let re = try!(Regex::new(r"(?m)^(\S+)-([^-\s]+)\s+<\s+needs updating \(port has (\S+)\)"));
+    Ok(re.captures_iter(output).map(|cap| PendingUpgrade {
+        name: cap[1].to_string(),
+        current_version: Some(cap[2].to_string()),
+        new_version: cap[3].to_string(),
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let output = "nginx-1.24.0_1                     <   needs updating (port has 1.24.0_2)\n\
+                      py39-pip-23.0.1                    <   needs updating (port has 23.3.1)\n";
+
+        assert_eq!(parse_version(output).unwrap(), vec![
+            PendingUpgrade {
+                name: "nginx".into(),
+                current_version: Some("1.24.0_1".into()),
+                new_version: "1.24.0_2".into(),
+            },
+            PendingUpgrade {
+                name: "py39-pip".into(),
+                current_version: Some("23.0.1".into()),
+                new_version: "23.3.1".into(),
+            },
+        ]);
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_install_version() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("make -C '/usr/ports/www/nginx' -V PKGVERSION", "1.24.0_2\n"),
+            mock::ok("make -C '/usr/ports/www/nginx' BATCH=yes install clean", ""),
+            mock::ok("make -C '/usr/ports/www/nginx' -V PKGVERSION", "1.24.0_2\n"),
+        ]);
+
+        assert_eq!(Ports.install_version(&mut host, "www/nginx", "1.24.*").unwrap().exit_code, 0);
+        assert!(Ports.install_version(&mut host, "www/nginx", "1.22.*").is_err());
+
+        agent_mock.join().unwrap();
     }
 }
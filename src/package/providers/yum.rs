@@ -11,7 +11,7 @@
 use command::{Command, CommandResult};
 use error::{Error, Result};
 use host::Host;
-use regex::Regex;
+use package::{InstalledPackage, PendingUpgrade, RepositorySpec};
 use super::*;
 
 pub struct Yum;
@@ -29,16 +29,33 @@ impl Provider for Yum {
     }
 
     fn is_installed(&self, host: &mut Host, name: &str) -> Result<bool> {
-        let cmd = Command::new("yum list installed");
+        Ok(try!(self.installed_version(host, name)).is_some())
+    }
+
+    fn installed_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("rpm -q --qf '%{{VERSION}}-%{{RELEASE}}\\n' {}", quote(name)));
+        Ok(parse_rpm_query(try!(cmd.exec(host))))
+    }
+
+    fn candidate_version(&self, host: &mut Host, name: &str) -> Result<Option<String>> {
+        let cmd = Command::new(&format!("yum -q list {}", quote(name)));
         let result = try!(cmd.exec(host));
+
+        // Exits non-zero if no package matches
         if result.exit_code != 0 {
-            return Err(Error::Agent(result.stderr));
+            return Ok(None);
         }
 
-        let arch = try!(needstr!(host.data() => "/_telemetry/os/arch"));
+        Ok(parse_rpm_list(&result.stdout, name))
+    }
+
+    fn installed_many(&self, host: &mut Host, names: &[&str]) -> Result<Vec<String>> {
+        let cmd = Command::new(&format!("rpm -q --qf '%{{NAME}}\\n' {}", quote_all(names)));
+        let result = try!(cmd.exec(host));
 
-        let re = try!(Regex::new(&format!("(?m)^{}\\.({}|noarch)\\s+", name, arch)));
-        Ok(re.is_match(&result.stdout))
+        // Missing packages are reported as "package <name> is not
+        // installed", which never matches a bare name.
+        Ok(result.stdout.lines().map(|l| l.trim().to_string()).filter(|l| names.contains(&l.as_str())).collect())
     }
 
     fn install(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
@@ -46,8 +63,113 @@ impl Provider for Yum {
         cmd.exec(host)
     }
 
+    fn install_version(&self, host: &mut Host, name: &str, version: &str) -> Result<CommandResult> {
+        let spec = quote(&format!("{}-{}", name, version));
+        let installed = try!(self.installed_version(host, name)).is_some();
+        let cmd = Command::new(&format!("yum -y {} {}", if installed { "downgrade" } else { "install" }, spec));
+        let result = try!(cmd.exec(host));
+
+        // `downgrade` refuses to upgrade, so fall back to `install`
+        if installed && result.exit_code != 0 {
+            let cmd = Command::new(&format!("yum -y install {}", spec));
+            return cmd.exec(host);
+        }
+
+        Ok(result)
+    }
+
+    fn install_many(&self, host: &mut Host, names: &[&str]) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("yum -y install {}", quote_all(names)));
+        cmd.exec(host)
+    }
+
+    fn upgrade(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("yum -y update {}", quote(name)));
+        cmd.exec(host)
+    }
+
     fn uninstall(&self, host: &mut Host, name: &str) -> Result<CommandResult> {
         let cmd = Command::new(&format!("yum -y remove {}", name));
         cmd.exec(host)
     }
+
+    fn add_repository(&self, host: &mut Host, name: &str, spec: &RepositorySpec) -> Result<bool> {
+        add_rpm_repository(host, name, spec)
+    }
+
+    fn remove_repository(&self, host: &mut Host, name: &str) -> Result<bool> {
+        remove_file(host, &format!("/etc/yum.repos.d/{}.repo", name))
+    }
+
+    fn refresh_cache(&self, host: &mut Host) -> Result<CommandResult> {
+        let cmd = Command::new("yum makecache");
+        cmd.exec(host)
+    }
+
+    fn pending_upgrades(&self, host: &mut Host, security_only: bool) -> Result<Vec<PendingUpgrade>> {
+        rpm_pending_upgrades(host, "yum", security_only)
+    }
+
+    fn upgrade_all(&self, host: &mut Host, security_only: bool) -> Result<CommandResult> {
+        let cmd = Command::new(&format!("yum -y{} update", if security_only { " --security" } else { "" }));
+        cmd.exec(host)
+    }
+
+    fn list_installed(&self, host: &mut Host) -> Result<Vec<InstalledPackage>> {
+        let cmd = Command::new("yum -q list installed");
+        let result = try!(cmd.exec(host));
+        if result.exit_code != 0 {
+            return Err(Error::Agent(result.stderr));
+        }
+
+        Ok(parse_list_installed(&result.stdout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    #[cfg(feature = "remote-run")]
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_is_installed() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("rpm -q --qf '%{VERSION}-%{RELEASE}\\n' 'nginx'", "1.20.1-10.el9\n"),
+            mock::err("rpm -q --qf '%{VERSION}-%{RELEASE}\\n' 'nginx-mod-mail'", 1, "package nginx-mod-mail is not installed\n"),
+        ]);
+
+        assert!(Yum.is_installed(&mut host, "nginx").unwrap());
+        assert!(!Yum.is_installed(&mut host, "nginx-mod-mail").unwrap());
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_installed_many() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::err("rpm -q --qf '%{NAME}\\n' 'git' 'gcc-c++'", 1, "git\npackage gcc-c++ is not installed\n"),
+        ]);
+
+        assert_eq!(Yum.installed_many(&mut host, &["git", "gcc-c++"]).unwrap(), vec!["git".to_string()]);
+
+        agent_mock.join().unwrap();
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_candidate_version() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("yum -q list 'nginx'", "Installed Packages\nnginx.x86_64  1:1.20.1-10.el9  @appstream\nAvailable Packages\nnginx.x86_64  1:1.20.1-14.el9  appstream\n"),
+            mock::err("yum -q list 'nosuchpkg'", 1, ""),
+        ]);
+
+        assert_eq!(Yum.candidate_version(&mut host, "nginx").unwrap(), Some("1.20.1-14.el9".into()));
+        assert_eq!(Yum.candidate_version(&mut host, "nosuchpkg").unwrap(), None);
+
+        agent_mock.join().unwrap();
+    }
 }
@@ -0,0 +1,156 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Third-party package repositories.
+
+use error::{Error, Result};
+use host::Host;
+use super::manager::refresh_cache;
+use super::providers::*;
+
+/// Definition of a package repository for `Repository::add`.
+///
+/// Which fields are required depends on the provider:
+///
+/// - Apt: `url`, `distribution` and usually `components`. The key is
+///   saved to `/etc/apt/keyrings` and referenced with `signed-by`.
+/// - Yum and Dnf: `url`, which is the repo's `baseurl`. Packages are
+///   only checked if `key_url` is set.
+/// - Pkg: `url`. Packages are only checked if `key_url` is set.
+/// - Homebrew: nothing, unless the tap isn't hosted on GitHub, in
+///   which case `url` is its Git remote.
+/// - Nix: `url`, which is the channel URL.
+///
+/// Pip, Gem, Npm and Cargo don't support repositories.
+///
+/// Signing keys are only downloaded if they don't already exist, so
+/// remove the repository first to rotate a key.
+#[derive(Clone, Debug, Default)]
+pub struct RepositorySpec {
+    /// Repository URL
+    pub url: Option<String>,
+    /// Apt distribution, e.g. `stable` or `bionic`
+    pub distribution: Option<String>,
+    /// Apt components, e.g. `main`
+    pub components: Vec<String>,
+    /// URL of the repository's signing key
+    pub key_url: Option<String>,
+}
+
+/// Primitive for managing third-party package repositories.
+///
+/// The package metadata cache is refreshed whenever a repository is
+/// added or removed, so its packages can be installed straight away.
+///
+///# Example
+///
+/// ```no_run
+/// # use inapi::{Host, Package, Providers, Repository, RepositorySpec};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let repo = Repository::new(&mut host, "nginx", Some(Providers::Apt)).unwrap();
+/// repo.add(&mut host, &RepositorySpec {
+///     url: Some("http://nginx.org/packages/ubuntu".into()),
+///     distribution: Some("bionic".into()),
+///     components: vec!["nginx".into()],
+///     key_url: Some("https://nginx.org/keys/nginx_signing.key".into()),
+/// }).unwrap();
+///
+/// let mut package = Package::new(&mut host, "nginx", Some(Providers::Apt)).unwrap();
+/// package.install(&mut host).unwrap();
+/// ```
+pub struct Repository {
+    /// Repository name, e.g. `nginx` or `homebrew/cask`
+    name: String,
+    /// The package source
+    provider: Box<Provider + 'static>,
+}
+
+impl Repository {
+    /// Create a new Repository.
+    ///
+    /// The name is used for the repository's config file, so may only
+    /// contain a `/` for Homebrew taps.
+    pub fn new(host: &mut Host, name: &str, providers: Option<Providers>) -> Result<Repository> {
+        let provider = try!(ProviderFactory::create(host, providers));
+
+        let valid = match provider.get_providers() {
+            Providers::Homebrew => name.splitn(2, '/').all(|p| is_valid_name(p)),
+            _ => is_valid_name(name),
+        };
+        if !valid {
+            return Err(Error::Generic(format!("Invalid repository name `{}`", name)));
+        }
+
+        Ok(Repository {
+            name: name.to_string(),
+            provider: provider,
+        })
+    }
+
+    /// Add the repository, or update it if it has changed. Returns
+    /// `true` if anything changed.
+    pub fn add(&self, host: &mut Host, spec: &RepositorySpec) -> Result<bool> {
+        if try!(self.provider.add_repository(host, &self.name, spec)) {
+            try!(refresh_cache(host, &*self.provider));
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Remove the repository. Returns `true` if it existed.
+    pub fn remove(&self, host: &mut Host) -> Result<bool> {
+        if try!(self.provider.remove_repository(host, &self.name)) {
+            try!(refresh_cache(host, &*self.provider));
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    use super::*;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("nginx-stable_1.0"));
+        assert!(!is_valid_name("../sources"));
+        assert!(!is_valid_name(".hidden"));
+        assert!(!is_valid_name(""));
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_add_tap() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("type brew", "/usr/local/bin/brew"),
+            mock::ok("brew tap", "homebrew/core\n"),
+            mock::ok("brew tap 'homebrew/cask'", ""),
+            mock::ok("brew update", ""),
+            mock::state_dir(),
+            mock::ok("date +%s > '/var/lib/inapi/homebrew-refreshed'", ""),
+            mock::ok("brew tap", "homebrew/cask\nhomebrew/core\n"),
+        ]);
+
+        let repo = Repository::new(&mut host, "homebrew/cask", Some(Providers::Homebrew)).unwrap();
+        assert!(repo.add(&mut host, &RepositorySpec::default()).unwrap());
+        assert!(!repo.add(&mut host, &RepositorySpec::default()).unwrap());
+
+        agent_mock.join().unwrap();
+    }
+}
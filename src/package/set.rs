@@ -0,0 +1,160 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Batch installation of packages.
+
+use command::CommandResult;
+use error::Result;
+use host::Host;
+use super::providers::*;
+
+/// Outcome of installing a package as part of a `PackageSet`.
+#[derive(Debug, PartialEq)]
+pub enum PackageOutcome {
+    /// Package was already installed, so was left alone
+    AlreadyInstalled,
+    /// Package was installed by the transaction
+    Installed,
+    /// Package is still missing after the transaction
+    Failed,
+}
+
+/// Result of `PackageSet::install`.
+pub struct PackageSetResult {
+    /// Outcome for each package, in the order they were given
+    pub outcomes: Vec<(String, PackageOutcome)>,
+    /// Result of the provider transaction, or `None` if every package
+    /// was already installed
+    pub result: Option<CommandResult>,
+}
+
+impl PackageSetResult {
+    /// Whether every package is now installed.
+    pub fn is_ok(&self) -> bool {
+        self.outcomes.iter().all(|&(_, ref o)| *o != PackageOutcome::Failed)
+    }
+}
+
+/// A group of packages that are installed together in a single
+/// provider transaction.
+///
+/// This is much faster than installing each `Package` separately, and
+/// lets the provider resolve dependencies across the whole set.
+///
+///# Example
+///
+/// ```no_run
+/// # use inapi::{Host, PackageOutcome, PackageSet};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let set = PackageSet::new(&mut host, &["nginx", "git", "curl"], None).unwrap();
+/// let result = set.install(&mut host).unwrap();
+///
+/// for (name, outcome) in result.outcomes {
+///     if outcome == PackageOutcome::Failed {
+///         println!("Failed to install {}", name);
+///     }
+/// }
+/// ```
+pub struct PackageSet {
+    /// Package names
+    names: Vec<String>,
+    /// The package source
+    provider: Box<Provider + 'static>,
+}
+
+impl PackageSet {
+    /// Create a new PackageSet.
+    pub fn new(host: &mut Host, names: &[&str], providers: Option<Providers>) -> Result<PackageSet> {
+        let provider = try!(ProviderFactory::create(host, providers));
+
+        Ok(PackageSet {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            provider: provider,
+        })
+    }
+
+    /// Get the names of the packages that are installed.
+    pub fn installed(&self, host: &mut Host) -> Result<Vec<String>> {
+        if self.names.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let names: Vec<&str> = self.names.iter().map(|n| n.as_str()).collect();
+        self.provider.installed_many(host, &names)
+    }
+
+    /// Install any packages that are missing in one transaction.
+    ///
+    /// A failed transaction isn't an error, as some packages may still
+    /// have been installed. Check each package's outcome instead.
+    pub fn install(&self, host: &mut Host) -> Result<PackageSetResult> {
+        let installed = try!(self.installed(host));
+        let missing: Vec<&str> = self.names.iter()
+                                           .filter(|n| !installed.contains(n))
+                                           .map(|n| n.as_str())
+                                           .collect();
+
+        if missing.is_empty() {
+            return Ok(PackageSetResult {
+                outcomes: self.names.iter().map(|n| (n.clone(), PackageOutcome::AlreadyInstalled)).collect(),
+                result: None,
+            });
+        }
+
+        let result = try!(self.provider.install_many(host, &missing));
+        let now_installed = try!(self.provider.installed_many(host, &missing));
+
+        Ok(PackageSetResult {
+            outcomes: self.names.iter().map(|n| {
+                let outcome = if installed.contains(n) {
+                    PackageOutcome::AlreadyInstalled
+                } else if now_installed.contains(n) {
+                    PackageOutcome::Installed
+                } else {
+                    PackageOutcome::Failed
+                };
+                (n.clone(), outcome)
+            }).collect(),
+            result: Some(result),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use package::mock;
+    #[cfg(feature = "remote-run")]
+    use super::*;
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_install() {
+        let (mut host, agent_mock) = mock::host(vec![
+            mock::ok("type apt-get", "/usr/bin/apt-get"),
+            mock::err("dpkg-query -W -f '${Package}\\t${Status}\\n' 'git' 'nginx' 'nosuchpkg'", 1, "git\tinstall ok installed\nnginx\tdeinstall ok config-files\n"),
+            mock::err("apt-get -y install 'nginx' 'nosuchpkg'", 100, ""),
+            mock::err("dpkg-query -W -f '${Package}\\t${Status}\\n' 'nginx' 'nosuchpkg'", 1, "nginx\tinstall ok installed\n"),
+        ]);
+
+        let set = PackageSet::new(&mut host, &["git", "nginx", "nosuchpkg"], Some(Providers::Apt)).unwrap();
+        let result = set.install(&mut host).unwrap();
+
+        assert!(!result.is_ok());
+        assert_eq!(result.outcomes, vec![
+            ("git".to_string(), PackageOutcome::AlreadyInstalled),
+            ("nginx".to_string(), PackageOutcome::Installed),
+            ("nosuchpkg".to_string(), PackageOutcome::Failed),
+        ]);
+        assert_eq!(result.result.unwrap().exit_code, 100);
+
+        agent_mock.join().unwrap();
+    }
+}
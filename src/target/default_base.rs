@@ -0,0 +1,769 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use archive::ArchiveFormat;
+use command::CommandResult;
+use config::{self, ConfigEdit, ConfigFormat};
+use directory::{self, mode};
+use error::{Error, Result};
+use file::{checksum_reader, FileEdit, FileOwner, FileStat, FileType};
+use host::Host;
+use host::telemetry::{FsMount, Netif};
+use libc;
+use link::LinkType;
+use package::providers::{ProviderFactory, Providers};
+use regex::Regex;
+use std::{env, fs, io, mem, process, ptr, str};
+use std::ffi::{CStr, CString};
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{symlink, FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use user::{Group, User};
+use hostname::get_hostname;
+use pnet::datalink::interfaces;
+
+pub fn default_provider(host: &mut Host, providers: Vec<Providers>) -> Result<Providers> {
+    for p in providers {
+        let provider = ProviderFactory::create(host, Some(p));
+
+        if provider.is_ok() {
+            return Ok(provider.unwrap().get_providers());
+        }
+    }
+
+    Err(Error::Generic("No package providers are available".to_string()))
+}
+
+pub fn archive_extract<P: AsRef<Path>>(path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()> {
+    let path = path.as_ref();
+    let dest = dest.as_ref();
+
+    match format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+            let mut cmd = process::Command::new("tar");
+            cmd.arg(if format == ArchiveFormat::TarGz { "-xzf" } else { "-xf" })
+               .arg(path)
+               .arg("-C")
+               .arg(dest);
+            if strip_components > 0 {
+                cmd.arg(format!("--strip-components={}", strip_components));
+            }
+            run_checked(&mut cmd)
+        },
+        ArchiveFormat::Zip if strip_components == 0 => {
+            run_checked(process::Command::new("unzip").arg("-o").arg("-q").arg(path).arg("-d").arg(dest))
+        },
+        ArchiveFormat::Zip => {
+            // unzip can't strip leading components, so extract to a
+            // scratch directory and move the remaining entries into
+            // place.
+            let scratch = try!(create_unique(&dest.join(".inapi-extract"), |p| fs::create_dir(p)));
+
+            let result = run_checked(process::Command::new("unzip").arg("-o").arg("-q").arg(path).arg("-d").arg(&scratch))
+                .and_then(|_| strip_into(&scratch, dest, strip_components));
+            try!(fs::remove_dir_all(&scratch));
+            result
+        },
+    }
+}
+
+/// Create a new entry at `path` plus a random suffix with `create`,
+/// trying another suffix if the name is taken. `create` must fail
+/// with `AlreadyExists` rather than reuse an existing entry.
+fn create_unique<F>(path: &Path, mut create: F) -> Result<PathBuf>
+    where F: FnMut(&Path) -> io::Result<()>
+{
+    let mut urandom = try!(fs::File::open("/dev/urandom"));
+
+    loop {
+        let mut rand = [0; 8];
+        try!(urandom.read_exact(&mut rand));
+
+        let mut name = path.as_os_str().to_os_string();
+        name.push(format!("-{}", rand.iter().map(|b| format!("{:02x}", b)).collect::<String>()));
+        let unique = PathBuf::from(name);
+
+        match create(&unique) {
+            Ok(()) => return Ok(unique),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Move the entries `strip` levels beneath `dir` into `dest`. Entries
+/// above that depth are dropped, as `tar --strip-components` does.
+fn strip_into(dir: &Path, dest: &Path, strip: u32) -> Result<()> {
+    for entry in try!(fs::read_dir(dir)) {
+        let entry = try!(entry);
+        if strip > 0 {
+            if try!(entry.file_type()).is_dir() {
+                try!(strip_into(&entry.path(), dest, strip - 1));
+            }
+        } else {
+            try!(merge_into(&entry.path(), &dest.join(entry.file_name())));
+        }
+    }
+
+    Ok(())
+}
+
+/// Move `src` to `dst`, merging directories and replacing anything
+/// else.
+fn merge_into(src: &Path, dst: &Path) -> Result<()> {
+    let src_is_dir = try!(fs::symlink_metadata(src)).is_dir();
+
+    match fs::symlink_metadata(dst) {
+        Ok(ref meta) if meta.is_dir() && src_is_dir => {
+            for entry in try!(fs::read_dir(src)) {
+                let entry = try!(entry);
+                try!(merge_into(&entry.path(), &dst.join(entry.file_name())));
+            }
+            return Ok(());
+        },
+        Ok(ref meta) if meta.is_dir() => try!(fs::remove_dir_all(dst)),
+        Ok(_) => try!(fs::remove_file(dst)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(try!(fs::rename(src, dst)))
+}
+
+pub fn archive_create<P: AsRef<Path>>(path: P, source: P, format: ArchiveFormat) -> Result<()> {
+    let path = path.as_ref();
+    let source = source.as_ref();
+
+    // Build the archive next to its destination, keeping the
+    // extension so that zip doesn't append its own.
+    let file_name = try!(path.file_name().ok_or(Error::Generic(format!("Invalid archive path `{}`", path.display()))));
+    let mut tmp = path.with_file_name(format!(".inapi-{}", file_name.to_string_lossy()));
+    if tmp.is_relative() {
+        tmp = try!(env::current_dir()).join(tmp);
+    }
+    if tmp.exists() {
+        try!(fs::remove_file(&tmp));
+    }
+
+    // Archive the source's entries by name rather than `.`, so that
+    // extracting with strip components behaves as expected.
+    let mut entries = Vec::new();
+    for entry in try!(fs::read_dir(source)) {
+        entries.push(try!(entry).file_name());
+    }
+    entries.sort();
+    if entries.is_empty() {
+        entries.push(".".into());
+    }
+
+    let result = match format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+            run_checked(process::Command::new("tar")
+                .arg(if format == ArchiveFormat::TarGz { "-czf" } else { "-cf" })
+                .arg(&tmp)
+                .arg("-C")
+                .arg(source)
+                .args(&entries))
+        },
+        ArchiveFormat::Zip => {
+            run_checked(process::Command::new("zip")
+                .arg("-q")
+                .arg("-r")
+                .arg("-y")
+                .arg(&tmp)
+                .args(&entries)
+                .current_dir(source))
+        },
+    };
+
+    match result.and_then(|_| fs::rename(&tmp, path).map_err(|e| e.into())) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        },
+    }
+}
+
+/// Error for features that the platform doesn't support.
+pub fn unsupported<T>(feature: &str) -> Result<T> {
+    Err(Error::Generic(format!("{} are not supported on this platform", feature)))
+}
+
+/// Run a command, returning its stderr as an error if it fails.
+fn run_checked(cmd: &mut process::Command) -> Result<()> {
+    let output = try!(cmd.output());
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Generic(format!("Command {:?} failed with error: {}", cmd, String::from_utf8_lossy(&output.stderr).trim())))
+    }
+}
+
+pub fn command_exec(cmd: &str) -> Result<CommandResult> {
+    let output = try!(process::Command::new("sh").arg("-c").arg(cmd).output());
+
+    Ok(CommandResult {
+        exit_code: output.status.code().unwrap(),
+        stdout: str::from_utf8(&output.stdout).unwrap().trim().to_string(),
+        stderr: str::from_utf8(&output.stderr).unwrap().trim().to_string(),
+    })
+}
+
+pub fn directory_is_directory<P: AsRef<Path>>(path: P) -> Result<bool> {
+    let meta = fs::metadata(path);
+    Ok(meta.is_err() || meta.unwrap().is_dir())
+}
+
+pub fn directory_list<P: AsRef<Path>>(path: P, recursive: bool) -> Result<Vec<String>> {
+    directory::list_local(path, recursive)
+}
+
+pub fn directory_set_owner_recursive<P: AsRef<Path>>(path: P, user: &str, group: &str) -> Result<u64> {
+    let uid = try!(user_uid(user));
+    let gid = try!(group_gid(group));
+    let mut changed = 0;
+
+    for entry in try!(recursive_entries(path.as_ref())) {
+        let meta = try!(fs::symlink_metadata(&entry));
+        if meta.uid() != uid || meta.gid() != gid {
+            let c_path = try!(CString::new(entry.as_os_str().as_bytes()));
+            // Use lchown so that symlinks are changed rather than
+            // their targets.
+            if unsafe { libc::lchown(c_path.as_ptr(), uid, gid) } != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
+
+pub fn directory_set_mode_recursive<P: AsRef<Path>>(path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+    let mut changed = 0;
+
+    for entry in try!(recursive_entries(path.as_ref())) {
+        let meta = try!(fs::symlink_metadata(&entry));
+
+        // Symlink permissions are meaningless and chmod would follow
+        // them, so skip them entirely.
+        let spec = if meta.file_type().is_symlink() {
+            None
+        } else if meta.is_dir() {
+            dir_mode
+        } else {
+            file_mode
+        };
+
+        if let Some(spec) = spec {
+            let current = meta.permissions().mode() & 0o7777;
+            let new = try!(mode::apply(spec, current, meta.is_dir()));
+            if new != current {
+                try!(fs::set_permissions(&entry, fs::Permissions::from_mode(new)));
+                changed += 1;
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+/// The path itself, followed by everything beneath it.
+fn recursive_entries(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = vec![path.to_path_buf()];
+    if try!(fs::symlink_metadata(path)).is_dir() {
+        for entry in try!(directory::list_local(path, true)) {
+            entries.push(path.join(entry.trim_right_matches('/')));
+        }
+    }
+    Ok(entries)
+}
+
+pub fn user_by_name(name: &str) -> Result<Option<User>> {
+    let c_name = try!(CString::new(name));
+    getpw(|pwd, buf, len, result| unsafe { libc::getpwnam_r(c_name.as_ptr(), pwd, buf, len, result) })
+}
+
+pub fn user_by_uid(uid: u32) -> Result<Option<User>> {
+    getpw(|pwd, buf, len, result| unsafe { libc::getpwuid_r(uid, pwd, buf, len, result) })
+}
+
+pub fn group_by_name(name: &str) -> Result<Option<Group>> {
+    let c_name = try!(CString::new(name));
+    getgr(|grp, buf, len, result| unsafe { libc::getgrnam_r(c_name.as_ptr(), grp, buf, len, result) })
+}
+
+pub fn group_by_gid(gid: u32) -> Result<Option<Group>> {
+    getgr(|grp, buf, len, result| unsafe { libc::getgrgid_r(gid, grp, buf, len, result) })
+}
+
+/// Call one of the reentrant `getpw*_r` functions, growing the buffer
+/// until the entry fits.
+fn getpw<F>(f: F) -> Result<Option<User>>
+    where F: Fn(*mut libc::passwd, *mut libc::c_char, libc::size_t, *mut *mut libc::passwd) -> libc::c_int
+{
+    let mut pwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+
+    loop {
+        match f(&mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) {
+            0 => break,
+            libc::ERANGE => {
+                let len = buf.len() * 2;
+                buf.resize(len, 0);
+            },
+            rc => return Err(io::Error::from_raw_os_error(rc).into()),
+        }
+    }
+
+    if result.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(User {
+            name: unsafe { CStr::from_ptr(pwd.pw_name) }.to_string_lossy().into_owned(),
+            uid: pwd.pw_uid,
+            gid: pwd.pw_gid,
+        }))
+    }
+}
+
+/// Call one of the reentrant `getgr*_r` functions, growing the buffer
+/// until the entry fits.
+fn getgr<F>(f: F) -> Result<Option<Group>>
+    where F: Fn(*mut libc::group, *mut libc::c_char, libc::size_t, *mut *mut libc::group) -> libc::c_int
+{
+    let mut grp: libc::group = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+
+    loop {
+        match f(&mut grp, buf.as_mut_ptr(), buf.len(), &mut result) {
+            0 => break,
+            libc::ERANGE => {
+                let len = buf.len() * 2;
+                buf.resize(len, 0);
+            },
+            rc => return Err(io::Error::from_raw_os_error(rc).into()),
+        }
+    }
+
+    if result.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(Group {
+            name: unsafe { CStr::from_ptr(grp.gr_name) }.to_string_lossy().into_owned(),
+            gid: grp.gr_gid,
+        }))
+    }
+}
+
+/// Look up a user name by UID, falling back to the UID itself if the
+/// user doesn't exist.
+pub fn user_name(uid: u32) -> Result<String> {
+    Ok(try!(user_by_uid(uid)).map_or(uid.to_string(), |u| u.name))
+}
+
+/// Look up a group name by GID, falling back to the GID itself if the
+/// group doesn't exist.
+pub fn group_name(gid: u32) -> Result<String> {
+    Ok(try!(group_by_gid(gid)).map_or(gid.to_string(), |g| g.name))
+}
+
+/// Resolve a user name or numeric UID to a UID.
+pub fn user_uid(user: &str) -> Result<u32> {
+    if let Ok(uid) = user.parse() {
+        return Ok(uid);
+    }
+
+    match try!(user_by_name(user)) {
+        Some(u) => Ok(u.uid),
+        None => Err(Error::Generic(format!("Unknown user `{}`", user))),
+    }
+}
+
+/// Resolve a group name or numeric GID to a GID.
+pub fn group_gid(group: &str) -> Result<u32> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+
+    match try!(group_by_name(group)) {
+        Some(g) => Ok(g.gid),
+        None => Err(Error::Generic(format!("Unknown group `{}`", group))),
+    }
+}
+
+pub fn directory_create<P: AsRef<Path>>(path: P, recursive: bool) -> Result<()> {
+    if recursive {
+        try!(fs::create_dir_all(path));
+    } else {
+        try!(fs::create_dir(path));
+    }
+
+    Ok(())
+}
+
+pub fn directory_delete<P: AsRef<Path>>(path: P, recursive: bool) -> Result<()> {
+    if recursive {
+        try!(fs::remove_dir_all(path));
+    } else {
+        try!(fs::remove_dir(path));
+    }
+
+    Ok(())
+}
+
+pub fn file_is_file<P: AsRef<Path>>(path: P) -> Result<bool> {
+    match fs::metadata(path) {
+        Ok(meta) => Ok(meta.is_file()),
+        // A missing file is still a valid path for a new file
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn file_exists<P: AsRef<Path>>(path: P) -> Result<bool> {
+    Ok(fs::metadata(path).is_ok())
+}
+
+pub fn file_delete<P: AsRef<Path>>(path: P) -> Result<()> {
+    try!(fs::remove_file(path));
+    Ok(())
+}
+
+pub fn file_mv<P: AsRef<Path>>(path: P, new_path: P) -> Result<()> {
+    Ok(try!(fs::rename(path, new_path)))
+}
+
+pub fn file_copy<P: AsRef<Path>>(path: P, new_path: P) -> Result<()> {
+    try!(fs::copy(path, new_path));
+    Ok(())
+}
+
+pub fn file_get_owner<P: AsRef<Path>>(path: P) -> Result<FileOwner> {
+    let meta = try!(fs::symlink_metadata(path));
+
+    Ok(FileOwner {
+        user_name: try!(user_name(meta.uid())),
+        user_uid: meta.uid() as u64,
+        group_name: try!(group_name(meta.gid())),
+        group_gid: meta.gid() as u64,
+    })
+}
+
+pub fn file_set_owner<P: AsRef<Path>>(path: P, user: &str, group: &str) -> Result<()> {
+    let uid = try!(user_uid(user));
+    let gid = try!(group_gid(group));
+    let c_path = try!(CString::new(path.as_ref().as_os_str().as_bytes()));
+
+    if unsafe { libc::chown(c_path.as_ptr(), uid, gid) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error().into())
+    }
+}
+
+pub fn file_stat<P: AsRef<Path>>(path: P) -> Result<FileStat> {
+    let meta = try!(fs::symlink_metadata(path));
+    let ft = meta.file_type();
+
+    let file_type = if ft.is_symlink() {
+        FileType::Symlink
+    } else if ft.is_dir() {
+        FileType::Directory
+    } else if ft.is_socket() {
+        FileType::Socket
+    } else if ft.is_fifo() {
+        FileType::Fifo
+    } else if ft.is_block_device() {
+        FileType::BlockDevice
+    } else if ft.is_char_device() {
+        FileType::CharDevice
+    } else {
+        FileType::File
+    };
+
+    Ok(FileStat {
+        file_type: file_type,
+        size: meta.len(),
+        mtime: meta.mtime(),
+        owner: FileOwner {
+            user_name: try!(user_name(meta.uid())),
+            user_uid: meta.uid() as u64,
+            group_name: try!(group_name(meta.gid())),
+            group_gid: meta.gid() as u64,
+        },
+        mode: mode_to_u16(meta.mode()),
+        inode: meta.ino(),
+    })
+}
+
+/// Follows symlinks, like `file_set_mode`, as a symlink's own mode is
+/// meaningless on most platforms.
+pub fn file_get_mode<P: AsRef<Path>>(path: P) -> Result<u16> {
+    Ok(mode_to_u16(try!(fs::metadata(path)).mode()))
+}
+
+pub fn file_set_mode<P: AsRef<Path>>(path: P, mode: u16) -> Result<()> {
+    let mode = try!(mode_from_u16(mode));
+    Ok(try!(fs::set_permissions(path, fs::Permissions::from_mode(mode))))
+}
+
+/// Convert permission bits to the API's mode representation, where
+/// the decimal digits are the octal mode, e.g. 0o4755 => 4755.
+fn mode_to_u16(mode: u32) -> u16 {
+    let mode = mode & 0o7777;
+    ((mode >> 9) * 1000 + (mode >> 6 & 7) * 100 + (mode >> 3 & 7) * 10 + (mode & 7)) as u16
+}
+
+/// Convert the API's mode representation to permission bits.
+fn mode_from_u16(mode: u16) -> Result<u32> {
+    let digits = mode.to_string();
+    match u32::from_str_radix(&digits, 8) {
+        Ok(bits) if bits <= 0o7777 => Ok(bits),
+        _ => Err(Error::Generic(format!("Invalid mode `{}`", mode))),
+    }
+}
+
+pub fn config_edit<P: AsRef<Path>>(path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool> {
+    let mut fh = try!(OpenOptions::new().read(true).write(true).create(true).open(path));
+    let mut contents = String::new();
+    try!(fh.read_to_string(&mut contents));
+
+    match try!(config::apply(format, &contents, edits)) {
+        Some(edited) => {
+            try!(fh.seek(SeekFrom::Start(0)));
+            try!(fh.set_len(edited.len() as u64));
+            try!(fh.write_all(edited.as_bytes()));
+            try!(fh.sync_data());
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+pub fn file_checksum<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut fh = try!(fs::File::open(path));
+    checksum_reader(&mut fh)
+}
+
+pub fn file_read<P: AsRef<Path>>(path: P, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let mut fh = try!(fs::File::open(path));
+    try!(fh.seek(SeekFrom::Start(offset)));
+
+    let mut buf = Vec::new();
+    try!(fh.take(len).read_to_end(&mut buf));
+    Ok(buf)
+}
+
+pub fn file_edit<P: AsRef<Path>>(path: P, edit: &FileEdit) -> Result<bool> {
+    let mut fh = try!(OpenOptions::new().read(true).write(true).open(path));
+    let mut contents = String::new();
+    try!(fh.read_to_string(&mut contents));
+
+    match try!(edit.apply(&contents)) {
+        Some(edited) => {
+            try!(fh.seek(SeekFrom::Start(0)));
+            try!(fh.set_len(edited.len() as u64));
+            try!(fh.write_all(edited.as_bytes()));
+            try!(fh.sync_data());
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+pub fn link_exists<P: AsRef<Path>>(path: P) -> Result<bool> {
+    Ok(fs::symlink_metadata(path).is_ok())
+}
+
+pub fn link_read<P: AsRef<Path>>(path: P) -> Result<Option<String>> {
+    match fs::symlink_metadata(&path) {
+        Ok(ref meta) if meta.file_type().is_symlink() => {
+            Ok(Some(try!(fs::read_link(path)).to_string_lossy().into_owned()))
+        },
+        Ok(_) => Ok(None),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn link_is_same_file<P: AsRef<Path>>(path: P, target: P) -> Result<bool> {
+    match (fs::symlink_metadata(path), fs::metadata(target)) {
+        (Ok(a), Ok(b)) => Ok(a.dev() == b.dev() && a.ino() == b.ino()),
+        _ => Ok(false),
+    }
+}
+
+pub fn link_create<P: AsRef<Path>>(path: P, target: P, link_type: LinkType) -> Result<()> {
+    // Create the link beside its final path, then rename it into
+    // place so that an existing link is replaced atomically. Both
+    // kinds of link fail rather than replace an existing entry, so
+    // the temporary path is always one we created.
+    let target = target.as_ref();
+    let mut base = path.as_ref().as_os_str().to_owned();
+    base.push(".inlink");
+    let tmp = try!(create_unique(Path::new(&base), |p| match link_type {
+        LinkType::Symbolic => symlink(target, p),
+        LinkType::Hard => fs::hard_link(target, p),
+    }));
+
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+pub fn link_delete<P: AsRef<Path>>(path: P) -> Result<()> {
+    try!(fs::remove_file(path));
+    Ok(())
+}
+
+pub fn service_action(name: &str, action: &str) -> Result<Option<CommandResult>> {
+    if action == "start" || action == "stop" {
+        let status = command_exec(&format!("service {} status", name))?;
+        // XXX Non-zero exit code may not necessarily indicate that the
+        // service is stopped?
+        if (status.exit_code == 0 && action == "start") || (status.exit_code != 0 && action == "stop") {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(try!(command_exec(&format!("service {} {}", name, action)))))
+}
+
+pub fn hostname() -> Result<String> {
+    match get_hostname() {
+        Some(name) => Ok(name),
+        None => Err(Error::Generic("Could not determine hostname".into())),
+    }
+}
+
+pub enum FsFieldOrder {
+    Filesystem,
+    Size,
+    Used,
+    Available,
+    Capacity,
+    Mount,
+    Blank,
+}
+
+pub fn fs() -> Result<Vec<FsMount>> {
+    self::parse_fs(vec![
+        self::FsFieldOrder::Filesystem,
+        self::FsFieldOrder::Size,
+        self::FsFieldOrder::Used,
+        self::FsFieldOrder::Available,
+        self::FsFieldOrder::Capacity,
+        self::FsFieldOrder::Mount,
+    ])
+}
+
+pub fn parse_fs(fields: Vec<FsFieldOrder>) -> Result<Vec<FsMount>> {
+    let mount_out = try!(process::Command::new("df").arg("-Pk").output());
+    let mount = try!(String::from_utf8(mount_out.stdout));
+
+    let mut pattern = "(?m)^".to_string();
+
+    for field in fields {
+        match field {
+            FsFieldOrder::Filesystem => pattern.push_str("(?P<fs>.+?)"),
+            FsFieldOrder::Size => pattern.push_str("(?P<size>[0-9]+)"),
+            FsFieldOrder::Used => pattern.push_str("(?P<used>[0-9]+)"),
+            FsFieldOrder::Available => pattern.push_str("(?P<available>[0-9]+)"),
+            FsFieldOrder::Capacity => pattern.push_str("(?P<capacity>[0-9]{1,3})%"),
+            FsFieldOrder::Mount => pattern.push_str("(?P<mount>/.*)"),
+            FsFieldOrder::Blank => pattern.push_str(r"[^\s]+"),
+        }
+
+        pattern.push_str(r"[\s]*");
+    }
+
+    pattern.push_str("$");
+
+    let regex = Regex::new(&pattern).unwrap();
+    let mut fs = vec!();
+
+    let lines: Vec<&str> = mount.lines().collect();
+    for line in lines {
+        if let Some(cap) = regex.captures(line) {
+            fs.push(FsMount {
+                filesystem: cap.name("fs").unwrap().as_str().to_string(),
+                mountpoint: cap.name("mount").unwrap().as_str().to_string(),
+                size: try!(cap.name("size").unwrap().as_str().parse::<u64>()),
+                used: try!(cap.name("used").unwrap().as_str().parse::<u64>()),
+                available: try!(cap.name("available").unwrap().as_str().parse::<u64>()),
+                capacity: try!(cap.name("capacity").unwrap().as_str().parse::<f32>())/100.0,
+            });
+        }
+    };
+
+    Ok(fs)
+}
+
+pub fn net() -> Vec<Netif> {
+    interfaces().into_iter()
+        .map(|iface| Netif {
+            name: iface.name,
+            index: iface.index,
+            mac: iface.mac.map(|addr| addr.to_string()),
+            ips: iface.ips.map(|ips| ips.into_iter().map(|ip| ip.to_string()).collect()),
+            flags: iface.flags,
+        })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_hostname() {
+        // XXX Not a proper test. Requires mocking.
+        assert!(hostname().is_ok());
+    }
+
+    #[test]
+    fn test_mode() {
+        assert_eq!(mode_to_u16(0o100644), 644);
+        assert_eq!(mode_to_u16(0o4755), 4755);
+        assert_eq!(mode_from_u16(4755).unwrap(), 0o4755);
+        assert_eq!(mode_from_u16(7).unwrap(), 0o7);
+        assert!(mode_from_u16(689).is_err());
+        assert!(mode_from_u16(17777).is_err());
+    }
+
+    #[test]
+    fn test_file_get_mode() {
+        let tempdir = TempDir::new("default_base_test_mode").unwrap();
+        let file = tempdir.path().join("file");
+        let link = tempdir.path().join("link");
+        fs::File::create(&file).unwrap();
+        file_set_mode(&file, 600).unwrap();
+        symlink(&file, &link).unwrap();
+
+        assert_eq!(file_get_mode(&link).unwrap(), 600);
+    }
+
+    #[test]
+    fn test_user_uid() {
+        assert_eq!(user_uid("root").unwrap(), 0);
+        assert_eq!(user_uid("1234").unwrap(), 1234);
+        assert!(user_uid("inapi-missing-user").is_err());
+        assert_eq!(user_name(0).unwrap(), "root");
+    }
+}
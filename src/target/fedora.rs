@@ -0,0 +1,371 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use archive::{ArchiveFormat, ArchiveTarget};
+use command::{CommandResult, CommandTarget};
+use config::{ConfigEdit, ConfigFormat, ConfigTarget};
+use directory::DirectoryTarget;
+use error::Result;
+use file::{AclEntry, FileEdit, FileTarget, FileOwner, FileStat};
+use host::Host;
+use link::{LinkTarget, LinkType};
+use package::PackageTarget;
+use package::providers::Providers;
+use serde_json;
+use service::ServiceTarget;
+use user::{Group, User, UserTarget};
+use std::env;
+use std::path::Path;
+use super::{default_base as default, linux_base as linux, redhat_base as redhat};
+use host::telemetry::{Cpu, Os, Telemetry, TelemetryTarget};
+
+pub struct FedoraTarget;
+
+//
+// Archive
+//
+
+impl<P: AsRef<Path>> ArchiveTarget<P> for FedoraTarget {
+    #[allow(unused_variables)]
+    fn archive_extract(host: &mut Host, path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()> {
+        default::archive_extract(path, dest, format, strip_components)
+    }
+
+    #[allow(unused_variables)]
+    fn archive_create(host: &mut Host, path: P, source: P, format: ArchiveFormat) -> Result<()> {
+        default::archive_create(path, source, format)
+    }
+}
+
+//
+// Command
+//
+
+impl CommandTarget for FedoraTarget {
+    #[allow(unused_variables)]
+    fn exec(host: &mut Host, cmd: &str) -> Result<CommandResult> {
+        default::command_exec(cmd)
+    }
+}
+
+//
+// Config
+//
+
+impl<P: AsRef<Path>> ConfigTarget<P> for FedoraTarget {
+    #[allow(unused_variables)]
+    fn config_edit(host: &mut Host, path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool> {
+        default::config_edit(path, format, edits)
+    }
+}
+
+//
+// Directory
+//
+
+impl<P: AsRef<Path>> DirectoryTarget<P> for FedoraTarget {
+    #[allow(unused_variables)]
+    fn directory_is_directory(host: &mut Host, path: P) -> Result<bool> {
+        default::directory_is_directory(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::file_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_create(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        default::directory_create(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_delete(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        default::directory_delete(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_mv(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        default::file_get_owner(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        default::file_set_owner(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        default::file_get_mode(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        default::file_set_mode(path, mode)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_list(host: &mut Host, path: P, recursive: bool) -> Result<Vec<String>> {
+        default::directory_list(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_owner_recursive(host: &mut Host, path: P, user: &str, group: &str) -> Result<u64> {
+        default::directory_set_owner_recursive(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_mode_recursive(host: &mut Host, path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+        default::directory_set_mode_recursive(path, file_mode, dir_mode)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_restore_selinux_context(host: &mut Host, path: P, recursive: bool) -> Result<bool> {
+        linux::file_restore_selinux_context(path, recursive)
+    }
+}
+
+//
+// File
+//
+
+impl<P: AsRef<Path>> FileTarget<P> for FedoraTarget {
+    #[allow(unused_variables)]
+    fn file_is_file(host: &mut Host, path: P) -> Result<bool> {
+        default::file_is_file(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::file_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_delete(host: &mut Host, path: P) -> Result<()> {
+        default::file_delete(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_mv(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_copy(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_copy(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        default::file_get_owner(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        default::file_set_owner(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        default::file_get_mode(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        default::file_set_mode(path, mode)
+    }
+
+    #[allow(unused_variables)]
+    fn file_checksum(host: &mut Host, path: P) -> Result<String> {
+        default::file_checksum(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_read(host: &mut Host, path: P, offset: u64, len: u64) -> Result<Vec<u8>> {
+        default::file_read(path, offset, len)
+    }
+
+    #[allow(unused_variables)]
+    fn file_edit(host: &mut Host, path: P, edit: &FileEdit) -> Result<bool> {
+        default::file_edit(path, edit)
+    }
+
+    #[allow(unused_variables)]
+    fn file_stat(host: &mut Host, path: P) -> Result<FileStat> {
+        default::file_stat(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_acl(host: &mut Host, path: P) -> Result<Vec<AclEntry>> {
+        linux::file_get_acl(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        linux::file_set_acl(path, entries)
+    }
+
+    #[allow(unused_variables)]
+    fn file_remove_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        linux::file_remove_acl(path, entries)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_xattr(host: &mut Host, path: P, name: &str) -> Result<Option<String>> {
+        linux::file_get_xattr(path, name)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_xattr(host: &mut Host, path: P, name: &str, value: &str) -> Result<()> {
+        linux::file_set_xattr(path, name, value)
+    }
+
+    #[allow(unused_variables)]
+    fn file_remove_xattr(host: &mut Host, path: P, name: &str) -> Result<()> {
+        linux::file_remove_xattr(path, name)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_selinux_context(host: &mut Host, path: P) -> Result<Option<String>> {
+        linux::file_get_selinux_context(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_selinux_context(host: &mut Host, path: P, context: &str) -> Result<()> {
+        linux::file_set_selinux_context(path, context)
+    }
+
+    #[allow(unused_variables)]
+    fn file_restore_selinux_context(host: &mut Host, path: P) -> Result<bool> {
+        linux::file_restore_selinux_context(path, false)
+    }
+}
+
+//
+// Link
+//
+
+impl<P: AsRef<Path>> LinkTarget<P> for FedoraTarget {
+    #[allow(unused_variables)]
+    fn link_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::link_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn link_read(host: &mut Host, path: P) -> Result<Option<String>> {
+        default::link_read(path)
+    }
+
+    #[allow(unused_variables)]
+    fn link_is_same_file(host: &mut Host, path: P, target: P) -> Result<bool> {
+        default::link_is_same_file(path, target)
+    }
+
+    #[allow(unused_variables)]
+    fn link_create(host: &mut Host, path: P, target: P, link_type: LinkType) -> Result<()> {
+        default::link_create(path, target, link_type)
+    }
+
+    #[allow(unused_variables)]
+    fn link_delete(host: &mut Host, path: P) -> Result<()> {
+        default::link_delete(path)
+    }
+}
+
+//
+// Package
+//
+
+impl PackageTarget for FedoraTarget {
+    fn default_provider(host: &mut Host) -> Result<Providers> {
+        default::default_provider(host, vec![Providers::Dnf, Providers::Yum])
+    }
+}
+
+//
+// Service
+//
+
+impl ServiceTarget for FedoraTarget {
+    #[allow(unused_variables)]
+    fn service_action(host: &mut Host, name: &str, action: &str) -> Result<Option<CommandResult>> {
+        if try!(linux::using_systemd()) {
+            linux::service_systemd(name, action)
+        } else {
+            redhat::service_init(name, action)
+        }
+    }
+}
+
+//
+// Telemetry
+//
+
+impl TelemetryTarget for FedoraTarget {
+    #[allow(unused_variables)]
+    fn telemetry_init(host: &mut Host) -> Result<serde_json::Value> {
+        let cpu_vendor = try!(linux::cpu_vendor());
+        let cpu_brand = try!(linux::cpu_brand_string());
+        let hostname = try!(default::hostname());
+        let (version_str, version_maj, version_min, version_patch) = try!(redhat::version());
+
+        let telemetry = Telemetry::new(
+            Cpu::new(
+                &cpu_vendor,
+                &cpu_brand,
+                try!(linux::cpu_cores())
+            ),
+            try!(default::fs()),
+            &hostname,
+            try!(linux::memory()),
+            default::net(),
+            Os::new(
+                env::consts::ARCH,
+                "redhat",
+                "fedora",
+                &version_str,
+                version_maj,
+                version_min,
+                version_patch
+            ),
+        );
+
+        Ok(serde_json::to_value(telemetry)?)
+    }
+}
+
+//
+// User
+//
+
+impl UserTarget for FedoraTarget {
+    #[allow(unused_variables)]
+    fn user_by_name(host: &mut Host, name: &str) -> Result<Option<User>> {
+        default::user_by_name(name)
+    }
+
+    #[allow(unused_variables)]
+    fn user_by_uid(host: &mut Host, uid: u32) -> Result<Option<User>> {
+        default::user_by_uid(uid)
+    }
+
+    #[allow(unused_variables)]
+    fn group_by_name(host: &mut Host, name: &str) -> Result<Option<Group>> {
+        default::group_by_name(name)
+    }
+
+    #[allow(unused_variables)]
+    fn group_by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>> {
+        default::group_by_gid(gid)
+    }
+}
@@ -0,0 +1,722 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use archive::{ArchiveFormat, ArchiveTarget};
+use command::{CommandResult, CommandTarget};
+use config::{ConfigEdit, ConfigFormat, ConfigTarget};
+use directory::DirectoryTarget;
+use error::Result;
+use file::{AclEntry, FileEdit, FileTarget, FileOwner, FileStat};
+use host::Host;
+use link::{LinkTarget, LinkType};
+use package::PackageTarget;
+use package::providers::Providers;
+use serde_json::Value;
+use service::ServiceTarget;
+use user::{Group, User, UserTarget};
+use std::fs;
+use std::path::Path;
+use std::sync::{Once, ONCE_INIT};
+use super::Target;
+use super::centos::CentosTarget;
+use super::debian::DebianTarget;
+use super::fedora::FedoraTarget;
+use super::redhat::RedhatTarget;
+use super::ubuntu::UbuntuTarget;
+use super::nixos::NixOsTarget;
+use host::telemetry::TelemetryTarget;
+
+static mut LINUX_PLATFORM: LinuxPlatform = LinuxPlatform::Centos;
+static INIT_FINGERPRINT: Once = ONCE_INIT;
+
+enum LinuxPlatform {
+    Centos,
+    Debian,
+    Fedora,
+    Redhat,
+    Ubuntu,
+    NixOs,
+}
+
+//
+// Archive
+//
+
+impl<P: AsRef<Path>> ArchiveTarget<P> for Target {
+    fn archive_extract(host: &mut Host, path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::archive_extract(host, path, dest, format, strip_components),
+            &LinuxPlatform::Debian => DebianTarget::archive_extract(host, path, dest, format, strip_components),
+            &LinuxPlatform::Fedora => FedoraTarget::archive_extract(host, path, dest, format, strip_components),
+            &LinuxPlatform::Redhat => RedhatTarget::archive_extract(host, path, dest, format, strip_components),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::archive_extract(host, path, dest, format, strip_components),
+            &LinuxPlatform::NixOs => NixOsTarget::archive_extract(host, path, dest, format, strip_components),
+        }
+    }
+
+    fn archive_create(host: &mut Host, path: P, source: P, format: ArchiveFormat) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::archive_create(host, path, source, format),
+            &LinuxPlatform::Debian => DebianTarget::archive_create(host, path, source, format),
+            &LinuxPlatform::Fedora => FedoraTarget::archive_create(host, path, source, format),
+            &LinuxPlatform::Redhat => RedhatTarget::archive_create(host, path, source, format),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::archive_create(host, path, source, format),
+            &LinuxPlatform::NixOs => NixOsTarget::archive_create(host, path, source, format),
+        }
+    }
+}
+
+//
+// Command
+//
+
+impl CommandTarget for Target {
+    fn exec(host: &mut Host, cmd: &str) -> Result<CommandResult> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::exec(host, cmd),
+            &LinuxPlatform::Debian => DebianTarget::exec(host, cmd),
+            &LinuxPlatform::Fedora => FedoraTarget::exec(host, cmd),
+            &LinuxPlatform::Redhat => RedhatTarget::exec(host, cmd),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::exec(host, cmd),
+            &LinuxPlatform::NixOs => NixOsTarget::exec(host, cmd),
+        }
+    }
+}
+
+//
+// Config
+//
+
+impl<P: AsRef<Path>> ConfigTarget<P> for Target {
+    fn config_edit(host: &mut Host, path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::config_edit(host, path, format, edits),
+            &LinuxPlatform::Debian => DebianTarget::config_edit(host, path, format, edits),
+            &LinuxPlatform::Fedora => FedoraTarget::config_edit(host, path, format, edits),
+            &LinuxPlatform::Redhat => RedhatTarget::config_edit(host, path, format, edits),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::config_edit(host, path, format, edits),
+            &LinuxPlatform::NixOs => NixOsTarget::config_edit(host, path, format, edits),
+        }
+    }
+}
+
+//
+// Directory
+//
+
+impl<P: AsRef<Path>> DirectoryTarget<P> for Target {
+    fn directory_is_directory(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_is_directory(host, path),
+            &LinuxPlatform::Debian => DebianTarget::directory_is_directory(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_is_directory(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_is_directory(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_is_directory(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_is_directory(host, path),
+        }
+    }
+
+    fn directory_exists(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_exists(host, path),
+            &LinuxPlatform::Debian => DebianTarget::directory_exists(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_exists(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_exists(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_exists(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_exists(host, path),
+        }
+    }
+
+    fn directory_create(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_create(host, path, recursive),
+            &LinuxPlatform::Debian => DebianTarget::directory_create(host, path, recursive),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_create(host, path, recursive),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_create(host, path, recursive),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_create(host, path, recursive),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_create(host, path, recursive),
+        }
+    }
+
+    fn directory_delete(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_delete(host, path, recursive),
+            &LinuxPlatform::Debian => DebianTarget::directory_delete(host, path, recursive),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_delete(host, path, recursive),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_delete(host, path, recursive),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_delete(host, path, recursive),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_delete(host, path, recursive),
+        }
+    }
+
+    fn directory_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_mv(host, path, new_path),
+            &LinuxPlatform::Debian => DebianTarget::directory_mv(host, path, new_path),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_mv(host, path, new_path),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_mv(host, path, new_path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_mv(host, path, new_path),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_mv(host, path, new_path),
+        }
+    }
+
+    fn directory_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_get_owner(host, path),
+            &LinuxPlatform::Debian => DebianTarget::directory_get_owner(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_get_owner(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_get_owner(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_get_owner(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_get_owner(host, path),
+        }
+    }
+
+    fn directory_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_set_owner(host, path, user, group),
+            &LinuxPlatform::Debian => DebianTarget::directory_set_owner(host, path, user, group),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_set_owner(host, path, user, group),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_set_owner(host, path, user, group),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_set_owner(host, path, user, group),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_set_owner(host, path, user, group),
+        }
+    }
+
+    fn directory_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_get_mode(host, path),
+            &LinuxPlatform::Debian => DebianTarget::directory_get_mode(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_get_mode(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_get_mode(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_get_mode(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_get_mode(host, path),
+        }
+    }
+
+    fn directory_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_set_mode(host, path, mode),
+            &LinuxPlatform::Debian => DebianTarget::directory_set_mode(host, path, mode),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_set_mode(host, path, mode),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_set_mode(host, path, mode),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_set_mode(host, path, mode),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_set_mode(host, path, mode),
+        }
+    }
+
+    fn directory_list(host: &mut Host, path: P, recursive: bool) -> Result<Vec<String>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_list(host, path, recursive),
+            &LinuxPlatform::Debian => DebianTarget::directory_list(host, path, recursive),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_list(host, path, recursive),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_list(host, path, recursive),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_list(host, path, recursive),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_list(host, path, recursive),
+        }
+    }
+
+    fn directory_set_owner_recursive(host: &mut Host, path: P, user: &str, group: &str) -> Result<u64> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_set_owner_recursive(host, path, user, group),
+            &LinuxPlatform::Debian => DebianTarget::directory_set_owner_recursive(host, path, user, group),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_set_owner_recursive(host, path, user, group),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_set_owner_recursive(host, path, user, group),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_set_owner_recursive(host, path, user, group),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_set_owner_recursive(host, path, user, group),
+        }
+    }
+
+    fn directory_set_mode_recursive(host: &mut Host, path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+            &LinuxPlatform::Debian => DebianTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_set_mode_recursive(host, path, file_mode, dir_mode),
+        }
+    }
+
+    fn directory_restore_selinux_context(host: &mut Host, path: P, recursive: bool) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::directory_restore_selinux_context(host, path, recursive),
+            &LinuxPlatform::Debian => DebianTarget::directory_restore_selinux_context(host, path, recursive),
+            &LinuxPlatform::Fedora => FedoraTarget::directory_restore_selinux_context(host, path, recursive),
+            &LinuxPlatform::Redhat => RedhatTarget::directory_restore_selinux_context(host, path, recursive),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::directory_restore_selinux_context(host, path, recursive),
+            &LinuxPlatform::NixOs => NixOsTarget::directory_restore_selinux_context(host, path, recursive),
+        }
+    }
+}
+
+//
+// File
+//
+
+impl<P: AsRef<Path>> FileTarget<P> for Target {
+    fn file_is_file(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_is_file(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_is_file(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_is_file(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_is_file(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_is_file(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_is_file(host, path),
+        }
+    }
+
+    fn file_exists(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_exists(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_exists(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_exists(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_exists(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_exists(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_exists(host, path),
+        }
+    }
+
+    fn file_delete(host: &mut Host, path: P) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_delete(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_delete(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_delete(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_delete(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_delete(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_delete(host, path),
+        }
+    }
+
+    fn file_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_mv(host, path, new_path),
+            &LinuxPlatform::Debian => DebianTarget::file_mv(host, path, new_path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_mv(host, path, new_path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_mv(host, path, new_path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_mv(host, path, new_path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_mv(host, path, new_path),
+        }
+    }
+
+    fn file_copy(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_copy(host, path, new_path),
+            &LinuxPlatform::Debian => DebianTarget::file_copy(host, path, new_path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_copy(host, path, new_path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_copy(host, path, new_path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_copy(host, path, new_path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_copy(host, path, new_path),
+        }
+    }
+
+    fn file_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_get_owner(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_get_owner(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_get_owner(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_get_owner(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_get_owner(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_get_owner(host, path),
+        }
+    }
+
+    fn file_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_set_owner(host, path, user, group),
+            &LinuxPlatform::Debian => DebianTarget::file_set_owner(host, path, user, group),
+            &LinuxPlatform::Fedora => FedoraTarget::file_set_owner(host, path, user, group),
+            &LinuxPlatform::Redhat => RedhatTarget::file_set_owner(host, path, user, group),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_set_owner(host, path, user, group),
+            &LinuxPlatform::NixOs => NixOsTarget::file_set_owner(host, path, user, group),
+        }
+    }
+
+    fn file_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_get_mode(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_get_mode(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_get_mode(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_get_mode(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_get_mode(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_get_mode(host, path),
+        }
+    }
+
+    fn file_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_set_mode(host, path, mode),
+            &LinuxPlatform::Debian => DebianTarget::file_set_mode(host, path, mode),
+            &LinuxPlatform::Fedora => FedoraTarget::file_set_mode(host, path, mode),
+            &LinuxPlatform::Redhat => RedhatTarget::file_set_mode(host, path, mode),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_set_mode(host, path, mode),
+            &LinuxPlatform::NixOs => NixOsTarget::file_set_mode(host, path, mode),
+        }
+    }
+
+    fn file_checksum(host: &mut Host, path: P) -> Result<String> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_checksum(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_checksum(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_checksum(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_checksum(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_checksum(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_checksum(host, path),
+        }
+    }
+
+    fn file_read(host: &mut Host, path: P, offset: u64, len: u64) -> Result<Vec<u8>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_read(host, path, offset, len),
+            &LinuxPlatform::Debian => DebianTarget::file_read(host, path, offset, len),
+            &LinuxPlatform::Fedora => FedoraTarget::file_read(host, path, offset, len),
+            &LinuxPlatform::Redhat => RedhatTarget::file_read(host, path, offset, len),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_read(host, path, offset, len),
+            &LinuxPlatform::NixOs => NixOsTarget::file_read(host, path, offset, len),
+        }
+    }
+
+    fn file_edit(host: &mut Host, path: P, edit: &FileEdit) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_edit(host, path, edit),
+            &LinuxPlatform::Debian => DebianTarget::file_edit(host, path, edit),
+            &LinuxPlatform::Fedora => FedoraTarget::file_edit(host, path, edit),
+            &LinuxPlatform::Redhat => RedhatTarget::file_edit(host, path, edit),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_edit(host, path, edit),
+            &LinuxPlatform::NixOs => NixOsTarget::file_edit(host, path, edit),
+        }
+    }
+
+    fn file_stat(host: &mut Host, path: P) -> Result<FileStat> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_stat(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_stat(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_stat(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_stat(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_stat(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_stat(host, path),
+        }
+    }
+
+    fn file_get_acl(host: &mut Host, path: P) -> Result<Vec<AclEntry>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_get_acl(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_get_acl(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_get_acl(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_get_acl(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_get_acl(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_get_acl(host, path),
+        }
+    }
+
+    fn file_set_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_set_acl(host, path, entries),
+            &LinuxPlatform::Debian => DebianTarget::file_set_acl(host, path, entries),
+            &LinuxPlatform::Fedora => FedoraTarget::file_set_acl(host, path, entries),
+            &LinuxPlatform::Redhat => RedhatTarget::file_set_acl(host, path, entries),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_set_acl(host, path, entries),
+            &LinuxPlatform::NixOs => NixOsTarget::file_set_acl(host, path, entries),
+        }
+    }
+
+    fn file_remove_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_remove_acl(host, path, entries),
+            &LinuxPlatform::Debian => DebianTarget::file_remove_acl(host, path, entries),
+            &LinuxPlatform::Fedora => FedoraTarget::file_remove_acl(host, path, entries),
+            &LinuxPlatform::Redhat => RedhatTarget::file_remove_acl(host, path, entries),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_remove_acl(host, path, entries),
+            &LinuxPlatform::NixOs => NixOsTarget::file_remove_acl(host, path, entries),
+        }
+    }
+
+    fn file_get_xattr(host: &mut Host, path: P, name: &str) -> Result<Option<String>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_get_xattr(host, path, name),
+            &LinuxPlatform::Debian => DebianTarget::file_get_xattr(host, path, name),
+            &LinuxPlatform::Fedora => FedoraTarget::file_get_xattr(host, path, name),
+            &LinuxPlatform::Redhat => RedhatTarget::file_get_xattr(host, path, name),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_get_xattr(host, path, name),
+            &LinuxPlatform::NixOs => NixOsTarget::file_get_xattr(host, path, name),
+        }
+    }
+
+    fn file_set_xattr(host: &mut Host, path: P, name: &str, value: &str) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_set_xattr(host, path, name, value),
+            &LinuxPlatform::Debian => DebianTarget::file_set_xattr(host, path, name, value),
+            &LinuxPlatform::Fedora => FedoraTarget::file_set_xattr(host, path, name, value),
+            &LinuxPlatform::Redhat => RedhatTarget::file_set_xattr(host, path, name, value),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_set_xattr(host, path, name, value),
+            &LinuxPlatform::NixOs => NixOsTarget::file_set_xattr(host, path, name, value),
+        }
+    }
+
+    fn file_remove_xattr(host: &mut Host, path: P, name: &str) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_remove_xattr(host, path, name),
+            &LinuxPlatform::Debian => DebianTarget::file_remove_xattr(host, path, name),
+            &LinuxPlatform::Fedora => FedoraTarget::file_remove_xattr(host, path, name),
+            &LinuxPlatform::Redhat => RedhatTarget::file_remove_xattr(host, path, name),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_remove_xattr(host, path, name),
+            &LinuxPlatform::NixOs => NixOsTarget::file_remove_xattr(host, path, name),
+        }
+    }
+
+    fn file_get_selinux_context(host: &mut Host, path: P) -> Result<Option<String>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_get_selinux_context(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_get_selinux_context(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_get_selinux_context(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_get_selinux_context(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_get_selinux_context(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_get_selinux_context(host, path),
+        }
+    }
+
+    fn file_set_selinux_context(host: &mut Host, path: P, context: &str) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_set_selinux_context(host, path, context),
+            &LinuxPlatform::Debian => DebianTarget::file_set_selinux_context(host, path, context),
+            &LinuxPlatform::Fedora => FedoraTarget::file_set_selinux_context(host, path, context),
+            &LinuxPlatform::Redhat => RedhatTarget::file_set_selinux_context(host, path, context),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_set_selinux_context(host, path, context),
+            &LinuxPlatform::NixOs => NixOsTarget::file_set_selinux_context(host, path, context),
+        }
+    }
+
+    fn file_restore_selinux_context(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::file_restore_selinux_context(host, path),
+            &LinuxPlatform::Debian => DebianTarget::file_restore_selinux_context(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::file_restore_selinux_context(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::file_restore_selinux_context(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::file_restore_selinux_context(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::file_restore_selinux_context(host, path),
+        }
+    }
+}
+
+//
+// Link
+//
+
+impl<P: AsRef<Path>> LinkTarget<P> for Target {
+    fn link_exists(host: &mut Host, path: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::link_exists(host, path),
+            &LinuxPlatform::Debian => DebianTarget::link_exists(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::link_exists(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::link_exists(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::link_exists(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::link_exists(host, path),
+        }
+    }
+
+    fn link_read(host: &mut Host, path: P) -> Result<Option<String>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::link_read(host, path),
+            &LinuxPlatform::Debian => DebianTarget::link_read(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::link_read(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::link_read(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::link_read(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::link_read(host, path),
+        }
+    }
+
+    fn link_is_same_file(host: &mut Host, path: P, target: P) -> Result<bool> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::link_is_same_file(host, path, target),
+            &LinuxPlatform::Debian => DebianTarget::link_is_same_file(host, path, target),
+            &LinuxPlatform::Fedora => FedoraTarget::link_is_same_file(host, path, target),
+            &LinuxPlatform::Redhat => RedhatTarget::link_is_same_file(host, path, target),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::link_is_same_file(host, path, target),
+            &LinuxPlatform::NixOs => NixOsTarget::link_is_same_file(host, path, target),
+        }
+    }
+
+    fn link_create(host: &mut Host, path: P, target: P, link_type: LinkType) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::link_create(host, path, target, link_type),
+            &LinuxPlatform::Debian => DebianTarget::link_create(host, path, target, link_type),
+            &LinuxPlatform::Fedora => FedoraTarget::link_create(host, path, target, link_type),
+            &LinuxPlatform::Redhat => RedhatTarget::link_create(host, path, target, link_type),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::link_create(host, path, target, link_type),
+            &LinuxPlatform::NixOs => NixOsTarget::link_create(host, path, target, link_type),
+        }
+    }
+
+    fn link_delete(host: &mut Host, path: P) -> Result<()> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::link_delete(host, path),
+            &LinuxPlatform::Debian => DebianTarget::link_delete(host, path),
+            &LinuxPlatform::Fedora => FedoraTarget::link_delete(host, path),
+            &LinuxPlatform::Redhat => RedhatTarget::link_delete(host, path),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::link_delete(host, path),
+            &LinuxPlatform::NixOs => NixOsTarget::link_delete(host, path),
+        }
+    }
+}
+
+//
+// Package
+//
+
+impl PackageTarget for Target {
+    fn default_provider(host: &mut Host) -> Result<Providers> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::default_provider(host),
+            &LinuxPlatform::Debian => DebianTarget::default_provider(host),
+            &LinuxPlatform::Fedora => FedoraTarget::default_provider(host),
+            &LinuxPlatform::Redhat => RedhatTarget::default_provider(host),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::default_provider(host),
+            &LinuxPlatform::NixOs => NixOsTarget::default_provider(host),
+        }
+    }
+}
+
+//
+// Service
+//
+
+impl ServiceTarget for Target {
+    fn service_action(host: &mut Host, name: &str, action: &str) -> Result<Option<CommandResult>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::service_action(host, name, action),
+            &LinuxPlatform::Debian => DebianTarget::service_action(host, name, action),
+            &LinuxPlatform::Fedora => FedoraTarget::service_action(host, name, action),
+            &LinuxPlatform::Redhat => RedhatTarget::service_action(host, name, action),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::service_action(host, name, action),
+            &LinuxPlatform::NixOs => NixOsTarget::service_action(host, name, action),
+        }
+    }
+}
+
+//
+// Telemetry
+//
+
+impl TelemetryTarget for Target {
+    fn telemetry_init(host: &mut Host) -> Result<Value> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::telemetry_init(host),
+            &LinuxPlatform::Debian => DebianTarget::telemetry_init(host),
+            &LinuxPlatform::Fedora => FedoraTarget::telemetry_init(host),
+            &LinuxPlatform::Redhat => RedhatTarget::telemetry_init(host),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::telemetry_init(host),
+            &LinuxPlatform::NixOs => NixOsTarget::telemetry_init(host),
+        }
+    }
+}
+
+fn fingerprint_os() -> &'static LinuxPlatform {
+    INIT_FINGERPRINT.call_once(|| {
+        // CentOS
+        if let Ok(_) = fs::metadata("/etc/centos-release") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::Centos; }
+        }
+        // Ubuntu
+        else if let Ok(_) = fs::metadata("/etc/lsb-release") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::Ubuntu; }
+        }
+        // Debian
+        else if let Ok(_) = fs::metadata("/etc/debian_version") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::Debian; }
+        }
+        // Fedora
+        else if let Ok(_) = fs::metadata("/etc/fedora-release") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::Fedora; }
+        }
+        // RedHat
+        else if let Ok(_) = fs::metadata("/etc/redhat-release") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::Redhat; }
+        }
+        // NixOS
+        else if let Ok(_) = fs::metadata("/etc/nixos/configuration.nix") {
+            unsafe { LINUX_PLATFORM = LinuxPlatform::NixOs; }
+        } else {
+            panic!("Unknown Linux distro");
+        }
+    });
+
+    unsafe { &LINUX_PLATFORM }
+}
+
+#[cfg(test)]
+mod tests {
+    use Host;
+    use package::PackageTarget;
+    use target::Target;
+    use host::telemetry::TelemetryTarget;
+
+    #[test]
+    fn test_package_default_provider() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+        let result = Target::default_provider(&mut host);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_telemetry_init() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+        let result = Target::telemetry_init(&mut host);
+        assert!(result.is_ok());
+    }
+}
+
+//
+// User
+//
+
+impl UserTarget for Target {
+    fn user_by_name(host: &mut Host, name: &str) -> Result<Option<User>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::user_by_name(host, name),
+            &LinuxPlatform::Debian => DebianTarget::user_by_name(host, name),
+            &LinuxPlatform::Fedora => FedoraTarget::user_by_name(host, name),
+            &LinuxPlatform::Redhat => RedhatTarget::user_by_name(host, name),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::user_by_name(host, name),
+            &LinuxPlatform::NixOs => NixOsTarget::user_by_name(host, name),
+        }
+    }
+
+    fn user_by_uid(host: &mut Host, uid: u32) -> Result<Option<User>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::user_by_uid(host, uid),
+            &LinuxPlatform::Debian => DebianTarget::user_by_uid(host, uid),
+            &LinuxPlatform::Fedora => FedoraTarget::user_by_uid(host, uid),
+            &LinuxPlatform::Redhat => RedhatTarget::user_by_uid(host, uid),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::user_by_uid(host, uid),
+            &LinuxPlatform::NixOs => NixOsTarget::user_by_uid(host, uid),
+        }
+    }
+
+    fn group_by_name(host: &mut Host, name: &str) -> Result<Option<Group>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::group_by_name(host, name),
+            &LinuxPlatform::Debian => DebianTarget::group_by_name(host, name),
+            &LinuxPlatform::Fedora => FedoraTarget::group_by_name(host, name),
+            &LinuxPlatform::Redhat => RedhatTarget::group_by_name(host, name),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::group_by_name(host, name),
+            &LinuxPlatform::NixOs => NixOsTarget::group_by_name(host, name),
+        }
+    }
+
+    fn group_by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>> {
+        match fingerprint_os() {
+            &LinuxPlatform::Centos => CentosTarget::group_by_gid(host, gid),
+            &LinuxPlatform::Debian => DebianTarget::group_by_gid(host, gid),
+            &LinuxPlatform::Fedora => FedoraTarget::group_by_gid(host, gid),
+            &LinuxPlatform::Redhat => RedhatTarget::group_by_gid(host, gid),
+            &LinuxPlatform::Ubuntu => UbuntuTarget::group_by_gid(host, gid),
+            &LinuxPlatform::NixOs => NixOsTarget::group_by_gid(host, gid),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use command::CommandResult;
+use error::{Error, Result};
+use file::AclEntry;
+use libc;
+use regex::Regex;
+use std::{io, process, ptr, str};
+use std::ffi::CString;
+use std::fs::File;
+use std::io::prelude::*;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use target::default_base as default;
+
+/// Name of the extended attribute that holds a file's SELinux
+/// context.
+const SELINUX_XATTR: &'static str = "security.selinux";
+
+pub fn file_get_acl<P: AsRef<Path>>(path: P) -> Result<Vec<AclEntry>> {
+    let output = try!(acl_command(process::Command::new("getfacl")
+        .arg("--omit-header")
+        .arg("--absolute-names")
+        .arg(path.as_ref())));
+
+    let mut entries = Vec::new();
+    for line in output.lines() {
+        // Strip comments, such as "#effective:r--"
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let entry: AclEntry = try!(line.parse());
+
+        // The base entries are just the file's mode, and the mask is
+        // recalculated by setfacl, so only report them for defaults.
+        if entry.default || entry.qualifier.is_some() {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+pub fn file_set_acl<P: AsRef<Path>>(path: P, entries: &[AclEntry]) -> Result<()> {
+    let spec: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+    try!(acl_command(process::Command::new("setfacl").arg("-m").arg(spec.join(",")).arg(path.as_ref())));
+    Ok(())
+}
+
+pub fn file_remove_acl<P: AsRef<Path>>(path: P, entries: &[AclEntry]) -> Result<()> {
+    let spec: Vec<String> = entries.iter().map(|e| e.subject()).collect();
+    try!(acl_command(process::Command::new("setfacl").arg("-x").arg(spec.join(",")).arg(path.as_ref())));
+    Ok(())
+}
+
+/// Run an ACL command, translating common failures into friendlier
+/// errors.
+fn acl_command(cmd: &mut process::Command) -> Result<String> {
+    let output = match cmd.output() {
+        Ok(o) => o,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(Error::Generic("ACLs are not supported on this host: getfacl/setfacl are not installed".into()));
+        },
+        Err(e) => return Err(e.into()),
+    };
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Operation not supported") {
+            Err(Error::Generic(format!("ACLs are not supported on this filesystem: {}", stderr.trim())))
+        } else {
+            Err(Error::Generic(format!("Could not change ACL: {}", stderr.trim())))
+        }
+    }
+}
+
+pub fn file_get_xattr<P: AsRef<Path>>(path: P, name: &str) -> Result<Option<String>> {
+    match try!(get_xattr(path.as_ref(), name).map_err(xattr_error)) {
+        Some(value) => Ok(Some(try!(String::from_utf8(value)))),
+        None => Ok(None),
+    }
+}
+
+pub fn file_set_xattr<P: AsRef<Path>>(path: P, name: &str, value: &str) -> Result<()> {
+    let c_path = try!(CString::new(path.as_ref().as_os_str().as_bytes()));
+    let c_name = try!(CString::new(name));
+
+    let rc = unsafe { libc::lsetxattr(c_path.as_ptr(), c_name.as_ptr(), value.as_ptr() as *const libc::c_void, value.len(), 0) };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(xattr_error(io::Error::last_os_error()))
+    }
+}
+
+pub fn file_remove_xattr<P: AsRef<Path>>(path: P, name: &str) -> Result<()> {
+    let c_path = try!(CString::new(path.as_ref().as_os_str().as_bytes()));
+    let c_name = try!(CString::new(name));
+
+    if unsafe { libc::lremovexattr(c_path.as_ptr(), c_name.as_ptr()) } == 0 {
+        Ok(())
+    } else {
+        Err(xattr_error(io::Error::last_os_error()))
+    }
+}
+
+/// Read an extended attribute without following symlinks. Returns
+/// `None` if the attribute doesn't exist.
+fn get_xattr(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let c_path = try!(CString::new(path.as_os_str().as_bytes()));
+    let c_name = try!(CString::new(name));
+
+    loop {
+        let size = unsafe { libc::lgetxattr(c_path.as_ptr(), c_name.as_ptr(), ptr::null_mut(), 0) };
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(libc::ENODATA) => Ok(None),
+                _ => Err(err),
+            };
+        }
+
+        let mut buf = vec![0u8; size as usize];
+        let read = unsafe { libc::lgetxattr(c_path.as_ptr(), c_name.as_ptr(), buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if read >= 0 {
+            buf.truncate(read as usize);
+            return Ok(Some(buf));
+        }
+
+        // The attribute grew between calls, so try again
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ERANGE) {
+            return Err(err);
+        }
+    }
+}
+
+fn xattr_error(err: io::Error) -> Error {
+    match err.raw_os_error() {
+        Some(libc::ENOTSUP) => Error::Generic("Extended attributes are not supported on this filesystem".into()),
+        _ => err.into(),
+    }
+}
+
+pub fn file_get_selinux_context<P: AsRef<Path>>(path: P) -> Result<Option<String>> {
+    match get_xattr(path.as_ref(), SELINUX_XATTR) {
+        Ok(Some(mut value)) => {
+            // The context is stored with a trailing NUL
+            if value.last() == Some(&0) {
+                value.pop();
+            }
+            Ok(Some(try!(String::from_utf8(value))))
+        },
+        Ok(None) => Ok(None),
+        // Filesystems without xattr support can't have a context
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOTSUP) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn file_set_selinux_context<P: AsRef<Path>>(path: P, context: &str) -> Result<()> {
+    try!(selinux_command(process::Command::new("chcon").arg("-h").arg(context).arg(path.as_ref())));
+    Ok(())
+}
+
+pub fn file_restore_selinux_context<P: AsRef<Path>>(path: P, recursive: bool) -> Result<bool> {
+    let mut cmd = process::Command::new("restorecon");
+    cmd.arg("-v");
+    if recursive {
+        cmd.arg("-R");
+    }
+    cmd.arg(path.as_ref());
+
+    // restorecon only prints the entries it relabels
+    Ok(!try!(selinux_command(&mut cmd)).trim().is_empty())
+}
+
+/// Run an SELinux command, translating common failures into
+/// friendlier errors.
+fn selinux_command(cmd: &mut process::Command) -> Result<String> {
+    let output = match cmd.output() {
+        Ok(o) => o,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(Error::Generic("SELinux is not supported on this host: policycoreutils is not installed".into()));
+        },
+        Err(e) => return Err(e.into()),
+    };
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::Generic(format!("Could not change SELinux context: {}", String::from_utf8_lossy(&output.stderr).trim())))
+    }
+}
+
+pub fn using_systemd() -> Result<bool> {
+    let output = process::Command::new("stat").args(&["--format=%N", "/proc/1/exe"]).output().unwrap();
+    if output.status.success() {
+        let out = try!(str::from_utf8(&output.stdout));
+        Ok(out.contains("systemd"))
+    } else {
+        Err(Error::Generic(try!(String::from_utf8(output.stdout))))
+    }
+}
+
+pub fn service_systemd(name: &str, action: &str) -> Result<Option<CommandResult>> {
+    match action {
+        "enable" | "disable" => {
+            let output = try!(process::Command::new("systemctl").arg("is-enabled").arg(name).output());
+            if (action == "enable" && output.status.success()) || (action == "disable" && !output.status.success()) {
+                return Ok(None);
+            }
+        },
+        "start" | "stop" => {
+            let output = try!(process::Command::new("systemctl").arg("is-active").arg(name).output());
+            if (action == "start" && output.status.success()) || (action == "stop" && !output.status.success()) {
+                return Ok(None);
+            }
+        },
+        _ => (),
+    }
+
+    Ok(Some(try!(default::command_exec(&format!("systemctl {} {}", action, name)))))
+}
+
+pub fn memory() -> Result<u64> {
+    let output = process::Command::new("free").arg("-b").output().unwrap();
+
+    if !output.status.success() {
+        return Err(Error::Generic("Could not determine memory".to_string()));
+    }
+
+    let regex = Regex::new(r"(?m)^Mem:\s+([0-9]+)").unwrap();
+    let capture = regex.captures(try!(str::from_utf8(&output.stdout)).trim());
+
+    if capture.is_some() {
+        Ok(capture.unwrap().get(1).unwrap().as_str().parse::<u64>().unwrap())
+    } else {
+        Err(Error::Generic("Invalid memory output".to_string()))
+    }
+}
+
+pub fn cpu_vendor() -> Result<String> {
+    get_cpu_item("vendor_id")
+}
+
+pub fn cpu_brand_string() -> Result<String> {
+    get_cpu_item("model name")
+}
+
+pub fn cpu_cores() -> Result<u32> {
+    Ok(try!(try!(get_cpu_item("cpu cores")).parse::<u32>()))
+}
+
+fn get_cpu_item(item: &str) -> Result<String> {
+    // XXX This result should be cached
+    let mut cpuinfo_f = try!(File::open("/proc/cpuinfo"));
+    let mut cpuinfo = String::new();
+    try!(cpuinfo_f.read_to_string(&mut cpuinfo));
+
+    let pattern = format!(r"(?m)^{}\s+: (.+)$", item);
+    let regex = Regex::new(&pattern).unwrap();
+    let capture = regex.captures(&cpuinfo);
+
+    if capture.is_some() {
+        Ok(capture.unwrap().get(1).unwrap().as_str().to_string())
+    } else {
+        Err(Error::Generic(format!("Could not find CPU item: {}", item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::get_cpu_item;
+
+    #[test]
+    fn test_memory() {
+        // XXX Not a proper test. Requires mocking.
+        assert!(memory().is_ok());
+    }
+
+    #[test]
+    fn test_cpu_vendor() {
+        // XXX Not a proper test. Requires mocking.
+        assert!(cpu_vendor().is_ok());
+    }
+
+    #[test]
+    fn test_cpu_brand_string() {
+        // XXX Not a proper test. Requires mocking.
+        assert!(cpu_brand_string().is_ok());
+    }
+
+    #[test]
+    fn test_cpu_cores() {
+        // XXX Not a proper test. Requires mocking.
+        assert!(cpu_cores().is_ok());
+    }
+
+    #[test]
+    fn test_get_cpu_item_fail() {
+        assert!(get_cpu_item("moocow").is_err());
+    }
+}
@@ -0,0 +1,440 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use archive::{ArchiveFormat, ArchiveTarget};
+use command::{CommandResult, CommandTarget};
+use config::{ConfigEdit, ConfigFormat, ConfigTarget};
+use directory::DirectoryTarget;
+use error::{Error, Result};
+use file::{AclEntry, FileEdit, FileTarget, FileOwner, FileStat};
+use host::Host;
+use link::{LinkTarget, LinkType};
+use host::telemetry::{Cpu, Os, Telemetry, TelemetryTarget};
+use package::PackageTarget;
+use package::providers::Providers;
+use serde_json;
+use service::ServiceTarget;
+use user::{Group, User, UserTarget};
+use std::{env, process, str};
+use std::path::Path;
+use super::{default_base as default, Target, unix_base as unix};
+
+// This implementation is legacy. More work is required to support
+// modern launchd implementations.
+//
+// const LD_PATHS: [&'static str; 2] = [
+//     "/Libarary/LaunchDaemons",
+//     "/System/Library/LaunchDaemons"
+// ];
+
+//
+// Archive
+//
+
+impl<P: AsRef<Path>> ArchiveTarget<P> for Target {
+    #[allow(unused_variables)]
+    fn archive_extract(host: &mut Host, path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()> {
+        default::archive_extract(path, dest, format, strip_components)
+    }
+
+    #[allow(unused_variables)]
+    fn archive_create(host: &mut Host, path: P, source: P, format: ArchiveFormat) -> Result<()> {
+        default::archive_create(path, source, format)
+    }
+}
+
+//
+// Command
+//
+
+impl CommandTarget for Target {
+    #[allow(unused_variables)]
+    fn exec(host: &mut Host, cmd: &str) -> Result<CommandResult> {
+        default::command_exec(cmd)
+    }
+}
+
+//
+// Config
+//
+
+impl<P: AsRef<Path>> ConfigTarget<P> for Target {
+    #[allow(unused_variables)]
+    fn config_edit(host: &mut Host, path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool> {
+        default::config_edit(path, format, edits)
+    }
+}
+
+//
+// Directory
+//
+
+impl<P: AsRef<Path>> DirectoryTarget<P> for Target {
+    #[allow(unused_variables)]
+    fn directory_is_directory(host: &mut Host, path: P) -> Result<bool> {
+        default::directory_is_directory(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::file_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_create(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        default::directory_create(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_delete(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        default::directory_delete(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_mv(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        default::file_get_owner(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        default::file_set_owner(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        default::file_get_mode(path)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        default::file_set_mode(path, mode)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_list(host: &mut Host, path: P, recursive: bool) -> Result<Vec<String>> {
+        default::directory_list(path, recursive)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_owner_recursive(host: &mut Host, path: P, user: &str, group: &str) -> Result<u64> {
+        default::directory_set_owner_recursive(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_set_mode_recursive(host: &mut Host, path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+        default::directory_set_mode_recursive(path, file_mode, dir_mode)
+    }
+
+    #[allow(unused_variables)]
+    fn directory_restore_selinux_context(host: &mut Host, path: P, recursive: bool) -> Result<bool> {
+        default::unsupported("SELinux contexts")
+    }
+}
+
+//
+// File
+//
+
+impl<P: AsRef<Path>> FileTarget<P> for Target {
+    #[allow(unused_variables)]
+    fn file_is_file(host: &mut Host, path: P) -> Result<bool> {
+        default::file_is_file(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::file_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_delete(host: &mut Host, path: P) -> Result<()> {
+        default::file_delete(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_mv(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_copy(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        default::file_copy(path, new_path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        default::file_get_owner(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        default::file_set_owner(path, user, group)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        default::file_get_mode(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        default::file_set_mode(path, mode)
+    }
+
+    #[allow(unused_variables)]
+    fn file_checksum(host: &mut Host, path: P) -> Result<String> {
+        default::file_checksum(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_read(host: &mut Host, path: P, offset: u64, len: u64) -> Result<Vec<u8>> {
+        default::file_read(path, offset, len)
+    }
+
+    #[allow(unused_variables)]
+    fn file_edit(host: &mut Host, path: P, edit: &FileEdit) -> Result<bool> {
+        default::file_edit(path, edit)
+    }
+
+    #[allow(unused_variables)]
+    fn file_stat(host: &mut Host, path: P) -> Result<FileStat> {
+        default::file_stat(path)
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_acl(host: &mut Host, path: P) -> Result<Vec<AclEntry>> {
+        default::unsupported("ACLs")
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        default::unsupported("ACLs")
+    }
+
+    #[allow(unused_variables)]
+    fn file_remove_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        default::unsupported("ACLs")
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_xattr(host: &mut Host, path: P, name: &str) -> Result<Option<String>> {
+        default::unsupported("Extended attributes")
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_xattr(host: &mut Host, path: P, name: &str, value: &str) -> Result<()> {
+        default::unsupported("Extended attributes")
+    }
+
+    #[allow(unused_variables)]
+    fn file_remove_xattr(host: &mut Host, path: P, name: &str) -> Result<()> {
+        default::unsupported("Extended attributes")
+    }
+
+    #[allow(unused_variables)]
+    fn file_get_selinux_context(host: &mut Host, path: P) -> Result<Option<String>> {
+        default::unsupported("SELinux contexts")
+    }
+
+    #[allow(unused_variables)]
+    fn file_set_selinux_context(host: &mut Host, path: P, context: &str) -> Result<()> {
+        default::unsupported("SELinux contexts")
+    }
+
+    #[allow(unused_variables)]
+    fn file_restore_selinux_context(host: &mut Host, path: P) -> Result<bool> {
+        default::unsupported("SELinux contexts")
+    }
+}
+
+//
+// Link
+//
+
+impl<P: AsRef<Path>> LinkTarget<P> for Target {
+    #[allow(unused_variables)]
+    fn link_exists(host: &mut Host, path: P) -> Result<bool> {
+        default::link_exists(path)
+    }
+
+    #[allow(unused_variables)]
+    fn link_read(host: &mut Host, path: P) -> Result<Option<String>> {
+        default::link_read(path)
+    }
+
+    #[allow(unused_variables)]
+    fn link_is_same_file(host: &mut Host, path: P, target: P) -> Result<bool> {
+        default::link_is_same_file(path, target)
+    }
+
+    #[allow(unused_variables)]
+    fn link_create(host: &mut Host, path: P, target: P, link_type: LinkType) -> Result<()> {
+        default::link_create(path, target, link_type)
+    }
+
+    #[allow(unused_variables)]
+    fn link_delete(host: &mut Host, path: P) -> Result<()> {
+        default::link_delete(path)
+    }
+}
+
+//
+// Package
+//
+
+impl PackageTarget for Target {
+    fn default_provider(host: &mut Host) -> Result<Providers> {
+        default::default_provider(host, vec![Providers::Homebrew, Providers::Macports])
+    }
+}
+
+//
+// Service
+//
+
+impl ServiceTarget for Target {
+    #[allow(unused_variables)]
+    fn service_action(host: &mut Host, name: &str, action: &str) -> Result<Option<CommandResult>> {
+        // This implementation is legacy. More work is required to
+        // support modern launchd implementations.
+        //
+        // let action = match action {
+        //     "start" => "load",
+        //     "stop" => "unload",
+        //     _ => action,
+        // };
+        //
+        // // If name is relative path, search known LaunchDaemon paths
+        // // for name's path.
+        // let mut name = name;
+        // let name_path = Path::new(name);
+        // if name_path.is_relative() {
+        //     for path in LD_PATHS.into_iter() {
+        //         let mut buf = PathBuf::from(path);
+        //
+        //         if buf.is_dir() {
+        //             buf.push(name);
+        //
+        //             if buf.is_file() {
+        //                 name = buf.to_str().unwrap();
+        //                 break;
+        //             }
+        //         }
+        //     }
+        // }
+        //
+        // command_exec(&format!("launchctl {} {}", action, name))
+        unimplemented!()
+    }
+}
+
+//
+// Telemetry
+//
+
+impl TelemetryTarget for Target {
+    #[allow(unused_variables)]
+    fn telemetry_init(host: &mut Host) -> Result<serde_json::Value> {
+        let cpu_vendor = try!(unix::get_sysctl_item("machdep\\.cpu\\.vendor"));
+        let cpu_brand = try!(unix::get_sysctl_item("machdep\\.cpu\\.brand_string"));
+        let hostname = try!(default::hostname());
+        let (version_str, version_maj, version_min, version_patch) = try!(version());
+
+        let telemetry = Telemetry::new(
+            Cpu::new(
+                &cpu_vendor,
+                &cpu_brand,
+                try!(try!(unix::get_sysctl_item("hw\\.physicalcpu")).parse::<u32>())
+            ),
+            try!(default::parse_fs(vec![
+                default::FsFieldOrder::Filesystem,
+                default::FsFieldOrder::Size,
+                default::FsFieldOrder::Used,
+                default::FsFieldOrder::Available,
+                default::FsFieldOrder::Capacity,
+                default::FsFieldOrder::Blank,
+                default::FsFieldOrder::Blank,
+                default::FsFieldOrder::Blank,
+                default::FsFieldOrder::Mount,
+            ])),
+            &hostname,
+            try!(try!(unix::get_sysctl_item("hw\\.memsize")).parse::<u64>()),
+            default::net(),
+            Os::new(env::consts::ARCH, "unix", "macos", &version_str, version_maj, version_min, version_patch),
+        );
+
+        Ok(serde_json::to_value(telemetry)?)
+    }
+}
+
+fn version() -> Result<(String, u32, u32, u32)> {
+    let out = process::Command::new("sw_vers").arg("-productVersion").output()?;
+    let version_str = str::from_utf8(&out.stdout).or(Err(Error::Generic("Could not read OS version".into())))?.trim().to_owned();
+    let (maj, min, patch) = {
+        let mut parts = version_str.split('.');
+        (parts.next().ok_or(Error::Generic(format!("Expected OS version format `u32.u32.u32`. Got: {}", version_str)))?.parse()?,
+         parts.next().ok_or(Error::Generic(format!("Expected OS version format `u32.u32.u32`. Got: {}", version_str)))?.parse()?,
+         parts.next().ok_or(Error::Generic(format!("Expected OS version format `u32.u32.u32`. Got: {}", version_str)))?.parse()?)
+    };
+    Ok((version_str, maj, min, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use Host;
+    use package::PackageTarget;
+    use target::Target;
+    use host::telemetry::TelemetryTarget;
+
+    #[test]
+    fn test_package_default_provider() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+        let result = Target::default_provider(&mut host);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_telemetry_init() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+        let result = Target::telemetry_init(&mut host);
+        assert!(result.is_ok());
+    }
+}
+
+//
+// User
+//
+
+impl UserTarget for Target {
+    #[allow(unused_variables)]
+    fn user_by_name(host: &mut Host, name: &str) -> Result<Option<User>> {
+        default::user_by_name(name)
+    }
+
+    #[allow(unused_variables)]
+    fn user_by_uid(host: &mut Host, uid: u32) -> Result<Option<User>> {
+        default::user_by_uid(uid)
+    }
+
+    #[allow(unused_variables)]
+    fn group_by_name(host: &mut Host, name: &str) -> Result<Option<Group>> {
+        default::group_by_name(name)
+    }
+
+    #[allow(unused_variables)]
+    fn group_by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>> {
+        default::group_by_gid(gid)
+    }
+}
@@ -0,0 +1,660 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use archive::{ArchiveFormat, ArchiveTarget};
+use command::{CommandResult, CommandTarget};
+use config::{ConfigEdit, ConfigFormat, ConfigTarget};
+use czmq::ZMsg;
+use directory::DirectoryTarget;
+use error::{Error, Result};
+use file::{AclEntry, FileEdit, FileTarget, FileOwner, FileStat};
+use host::{Host, HostSendRecv, TelemetryTarget};
+use link::{LinkTarget, LinkType};
+use package::PackageTarget;
+use package::providers::Providers;
+use serde_json::{self, Value};
+use service::ServiceTarget;
+use user::{Group, User, UserTarget};
+use std::path::Path;
+use super::Target;
+
+//
+// Archive
+//
+
+impl <P: AsRef<Path>> ArchiveTarget<P> for Target {
+    fn archive_extract(host: &mut Host, path: P, dest: P, format: ArchiveFormat, strip_components: u32) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("archive::extract"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(dest.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&format.to_string()));
+        try!(msg.addstr(&strip_components.to_string()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn archive_create(host: &mut Host, path: P, source: P, format: ArchiveFormat) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("archive::create"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(source.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&format.to_string()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+}
+
+//
+// Command
+//
+
+impl CommandTarget for Target {
+    fn exec(host: &mut Host, cmd: &str) -> Result<CommandResult> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("command::exec"));
+        try!(msg.addstr(cmd));
+        try!(host.send(msg));
+
+        let msg = try!(host.recv(3, Some(3)));
+
+        let exit_code = try!(msg.popstr().unwrap().or(Err(Error::HostResponse))).parse::<i32>().unwrap();
+        let stdout = try!(msg.popstr().unwrap().or(Err(Error::HostResponse)));
+        let stderr = try!(msg.popstr().unwrap().or(Err(Error::HostResponse)));
+
+        Ok(CommandResult {
+            exit_code: exit_code,
+            stdout: stdout,
+            stderr: stderr,
+        })
+    }
+}
+
+//
+// Config
+//
+
+impl <P: AsRef<Path>> ConfigTarget<P> for Target {
+    fn config_edit(host: &mut Host, path: P, format: ConfigFormat, edits: &[ConfigEdit]) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("config::edit"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&try!(serde_json::to_string(&format))));
+        try!(msg.addstr(&try!(serde_json::to_string(&edits))));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+}
+
+//
+// Directory
+//
+
+impl <P: AsRef<Path>> DirectoryTarget<P> for Target {
+    fn directory_is_directory(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::is_directory"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn directory_exists(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::exists"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn directory_create(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::create"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(if recursive { "1" } else { "0" }));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn directory_delete(host: &mut Host, path: P, recursive: bool) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::delete"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(if recursive { "1" } else { "0" }));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn directory_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::mv"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(new_path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn directory_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::get_owner"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(4, Some(4)));
+
+        Ok(FileOwner {
+            user_name: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))),
+            user_uid: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>().unwrap(),
+            group_name: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))),
+            group_gid: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>().unwrap()
+        })
+    }
+
+    fn directory_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::set_owner"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(user));
+        try!(msg.addstr(group));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn directory_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::get_mode"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(0, None));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u16>().unwrap())
+    }
+
+    fn directory_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::set_mode"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&mode.to_string()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn directory_list(host: &mut Host, path: P, recursive: bool) -> Result<Vec<String>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::list"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(if recursive { "1" } else { "0" }));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn directory_set_owner_recursive(host: &mut Host, path: P, user: &str, group: &str) -> Result<u64> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::set_owner_recursive"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(user));
+        try!(msg.addstr(group));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>()))
+    }
+
+    fn directory_set_mode_recursive(host: &mut Host, path: P, file_mode: Option<&str>, dir_mode: Option<&str>) -> Result<u64> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::set_mode_recursive"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(file_mode.unwrap_or("")));
+        try!(msg.addstr(dir_mode.unwrap_or("")));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>()))
+    }
+
+    fn directory_restore_selinux_context(host: &mut Host, path: P, recursive: bool) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("directory::restore_selinux_context"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(if recursive { "1" } else { "0" }));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+}
+
+//
+// File
+//
+
+impl <P: AsRef<Path>> FileTarget<P> for Target {
+    fn file_is_file(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::is_file"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(0, None));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn file_exists(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::exists"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn file_delete(host: &mut Host, path: P) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::delete"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_mv(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::mv"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(new_path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_copy(host: &mut Host, path: P, new_path: P) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::copy"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(new_path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_get_owner(host: &mut Host, path: P) -> Result<FileOwner> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::get_owner"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(4, Some(4)));
+
+        Ok(FileOwner {
+            user_name: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))),
+            user_uid: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>().unwrap(),
+            group_name: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))),
+            group_gid: try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u64>().unwrap()
+        })
+    }
+
+    fn file_set_owner(host: &mut Host, path: P, user: &str, group: &str) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::set_owner"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(user));
+        try!(msg.addstr(group));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_get_mode(host: &mut Host, path: P) -> Result<u16> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::get_mode"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(0, None));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))).parse::<u16>().unwrap())
+    }
+
+    fn file_set_mode(host: &mut Host, path: P, mode: u16) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::set_mode"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&mode.to_string()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_checksum(host: &mut Host, path: P) -> Result<String> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::checksum"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))
+    }
+
+    fn file_read(host: &mut Host, path: P, offset: u64, len: u64) -> Result<Vec<u8>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::read"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&offset.to_string()));
+        try!(msg.addstr(&len.to_string()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popbytes()).unwrap_or(Vec::new()))
+    }
+
+    fn file_edit(host: &mut Host, path: P, edit: &FileEdit) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::edit"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&try!(serde_json::to_string(edit))));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn file_stat(host: &mut Host, path: P) -> Result<FileStat> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::stat"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn file_get_acl(host: &mut Host, path: P) -> Result<Vec<AclEntry>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::get_acl"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn file_set_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::set_acl"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&try!(serde_json::to_string(entries))));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_remove_acl(host: &mut Host, path: P, entries: &[AclEntry]) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::remove_acl"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(&try!(serde_json::to_string(entries))));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_get_xattr(host: &mut Host, path: P, name: &str) -> Result<Option<String>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::get_xattr"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(name));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(2)));
+        if try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1" {
+            Ok(Some(try!(reply.popstr().unwrap().or(Err(Error::HostResponse)))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn file_set_xattr(host: &mut Host, path: P, name: &str, value: &str) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::set_xattr"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(name));
+        try!(msg.addstr(value));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_remove_xattr(host: &mut Host, path: P, name: &str) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::remove_xattr"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(name));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_get_selinux_context(host: &mut Host, path: P) -> Result<Option<String>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::get_selinux_context"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(2)));
+        if try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1" {
+            Ok(Some(try!(reply.popstr().unwrap().or(Err(Error::HostResponse)))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn file_set_selinux_context(host: &mut Host, path: P, context: &str) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::set_selinux_context"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(context));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn file_restore_selinux_context(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("file::restore_selinux_context"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+}
+
+//
+// Link
+//
+
+impl <P: AsRef<Path>> LinkTarget<P> for Target {
+    fn link_exists(host: &mut Host, path: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("link::exists"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn link_read(host: &mut Host, path: P) -> Result<Option<String>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("link::read"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(2)));
+        if try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1" {
+            Ok(Some(try!(reply.popstr().unwrap().or(Err(Error::HostResponse)))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn link_is_same_file(host: &mut Host, path: P, target: P) -> Result<bool> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("link::is_same_file"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(target.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(reply.popstr().unwrap().or(Err(Error::HostResponse))) == "1")
+    }
+
+    fn link_create(host: &mut Host, path: P, target: P, link_type: LinkType) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("link::create"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(msg.addstr(target.as_ref().to_str().unwrap()));
+        try!(msg.addstr(match link_type {
+            LinkType::Symbolic => "symbolic",
+            LinkType::Hard => "hard",
+        }));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+
+    fn link_delete(host: &mut Host, path: P) -> Result<()> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("link::delete"));
+        try!(msg.addstr(path.as_ref().to_str().unwrap()));
+        try!(host.send(msg));
+        try!(host.recv(0, None));
+        Ok(())
+    }
+}
+
+//
+// Package
+//
+
+impl PackageTarget for Target {
+    fn default_provider(host: &mut Host) -> Result<Providers> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("package::default_provider"));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(Providers::from(try!(reply.popstr().unwrap().or(Err(Error::HostResponse)))))
+    }
+}
+
+//
+// Service
+//
+
+impl ServiceTarget for Target {
+    fn service_action(host: &mut Host, name: &str, action: &str) -> Result<Option<CommandResult>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("service::action"));
+        try!(msg.addstr(name));
+        try!(msg.addstr(action));
+        try!(host.send(msg));
+
+        let msg = try!(host.recv(0, Some(3)));
+
+        if msg.size() == 0 {
+            Ok(None)
+        }
+        else if msg.size() == 3 {
+            Ok(Some(CommandResult {
+                exit_code: try!(msg.popstr().unwrap().or(Err(Error::HostResponse))).parse::<i32>().unwrap(),
+                stdout: try!(msg.popstr().unwrap().or(Err(Error::HostResponse))),
+                stderr: try!(msg.popstr().unwrap().or(Err(Error::HostResponse))),
+            }))
+        } else {
+            Err(Error::HostResponse)
+        }
+    }
+}
+
+//
+// Telemetry
+//
+
+impl TelemetryTarget for Target {
+    fn telemetry_init(host: &mut Host) -> Result<Value> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("telemetry"));
+        try!(host.send(msg));
+
+        let msg = try!(host.recv(1, Some(1)));
+        let telemetry = try!(msg.popstr().unwrap().or(Err(Error::HostResponse)));
+        Ok(try!(serde_json::from_str(&telemetry)))
+    }
+}
+
+//
+// User
+//
+
+impl UserTarget for Target {
+    fn user_by_name(host: &mut Host, name: &str) -> Result<Option<User>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("user::by_name"));
+        try!(msg.addstr(name));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn user_by_uid(host: &mut Host, uid: u32) -> Result<Option<User>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("user::by_uid"));
+        try!(msg.addstr(&uid.to_string()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn group_by_name(host: &mut Host, name: &str) -> Result<Option<Group>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("group::by_name"));
+        try!(msg.addstr(name));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+
+    fn group_by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>> {
+        let msg = ZMsg::new();
+        try!(msg.addstr("group::by_gid"));
+        try!(msg.addstr(&gid.to_string()));
+        try!(host.send(msg));
+
+        let reply = try!(host.recv(1, Some(1)));
+        Ok(try!(serde_json::from_str(&try!(reply.popstr().unwrap().or(Err(Error::HostResponse))))))
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2015-2017 Intecture Developers. See the COPYRIGHT file at the
+// top-level directory of this distribution and at
+// https://intecture.io/COPYRIGHT.
+//
+// Licensed under the Mozilla Public License 2.0 <LICENSE or
+// https://www.tldrlegal.com/l/mpl-2.0>. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! User and group lookups.
+
+use error::Result;
+use host::Host;
+use target::Target;
+
+/// A user account on a managed host.
+///
+///# Example
+///
+/// ```no_run
+/// # use inapi::{Host, User};
+#[cfg_attr(feature = "local-run", doc = "# let path: Option<String> = None;")]
+#[cfg_attr(feature = "local-run", doc = "# let mut host = Host::local(path).unwrap();")]
+#[cfg_attr(feature = "remote-run", doc = "# let mut host = Host::connect(\"hosts/myhost.json\").unwrap();")]
+/// let www = User::by_name(&mut host, "www-data").unwrap().expect("www-data user is missing");
+/// assert_eq!(User::by_uid(&mut host, www.uid).unwrap(), Some(www));
+/// ```
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    /// User name
+    pub name: String,
+    /// User UID
+    pub uid: u32,
+    /// User's primary group GID
+    pub gid: u32,
+}
+
+impl User {
+    /// Look up a user by name. Returns `None` if the user doesn't
+    /// exist.
+    pub fn by_name(host: &mut Host, name: &str) -> Result<Option<User>> {
+        Target::user_by_name(host, name)
+    }
+
+    /// Look up a user by UID. Returns `None` if the user doesn't
+    /// exist.
+    pub fn by_uid(host: &mut Host, uid: u32) -> Result<Option<User>> {
+        Target::user_by_uid(host, uid)
+    }
+}
+
+/// A group on a managed host.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    /// Group name
+    pub name: String,
+    /// Group GID
+    pub gid: u32,
+}
+
+impl Group {
+    /// Look up a group by name. Returns `None` if the group doesn't
+    /// exist.
+    pub fn by_name(host: &mut Host, name: &str) -> Result<Option<Group>> {
+        Target::group_by_name(host, name)
+    }
+
+    /// Look up a group by GID. Returns `None` if the group doesn't
+    /// exist.
+    pub fn by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>> {
+        Target::group_by_gid(host, gid)
+    }
+}
+
+pub trait UserTarget {
+    fn user_by_name(host: &mut Host, name: &str) -> Result<Option<User>>;
+    fn user_by_uid(host: &mut Host, uid: u32) -> Result<Option<User>>;
+    fn group_by_name(host: &mut Host, name: &str) -> Result<Option<Group>>;
+    fn group_by_gid(host: &mut Host, gid: u32) -> Result<Option<Group>>;
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "remote-run")]
+    use czmq::{ZMsg, ZSys};
+    use host::Host;
+    #[cfg(feature = "remote-run")]
+    use std::thread;
+    use super::*;
+
+    #[cfg(feature = "local-run")]
+    #[test]
+    fn test_lookup() {
+        let path: Option<String> = None;
+        let mut host = Host::local(path).unwrap();
+
+        let root = User::by_uid(&mut host, 0).unwrap().unwrap();
+        assert_eq!(root.name, "root");
+        assert_eq!(User::by_name(&mut host, "root").unwrap(), Some(root));
+        assert!(User::by_name(&mut host, "inapi-missing-user").unwrap().is_none());
+
+        let group = Group::by_gid(&mut host, 0).unwrap().unwrap();
+        assert_eq!(Group::by_name(&mut host, &group.name).unwrap(), Some(group));
+        assert!(Group::by_name(&mut host, "inapi-missing-group").unwrap().is_none());
+    }
+
+    #[cfg(feature = "remote-run")]
+    #[test]
+    fn test_lookup() {
+        ZSys::init();
+
+        let (client, mut server) = ZSys::create_pipe().unwrap();
+
+        let agent_mock = thread::spawn(move || {
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("user::by_name", msg.popstr().unwrap().unwrap());
+            assert_eq!("www-data", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("{\"name\":\"www-data\",\"uid\":33,\"gid\":33}").unwrap();
+            reply.send(&mut server).unwrap();
+
+            let msg = ZMsg::recv(&mut server).unwrap();
+            assert_eq!("group::by_gid", msg.popstr().unwrap().unwrap());
+            assert_eq!("1234", msg.popstr().unwrap().unwrap());
+
+            let reply = ZMsg::new();
+            reply.addstr("Ok").unwrap();
+            reply.addstr("null").unwrap();
+            reply.send(&mut server).unwrap();
+        });
+
+        let mut host = Host::test_new(None, Some(client), None, None);
+
+        let user = User::by_name(&mut host, "www-data").unwrap().unwrap();
+        assert_eq!(user.uid, 33);
+        assert!(Group::by_gid(&mut host, 1234).unwrap().is_none());
+
+        agent_mock.join().unwrap();
+    }
+}